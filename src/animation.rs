@@ -1,6 +1,10 @@
+use std::collections::VecDeque;
 use std::f64::consts::PI;
 use std::time::{Duration, Instant};
 
+use webrender::api::units::{LayoutPixel, LayoutTransform, LayoutVector3D};
+use webrender::euclid::Rotation3D;
+
 #[derive(Clone)]
 pub struct AnimationCurve {
     curve: fn(f64) -> f64,
@@ -21,6 +25,14 @@ impl AnimationCurve {
     };
 }
 
+// what happens once the target is reached
+#[derive(Clone, Copy, PartialEq)]
+pub enum RepeatMode {
+    Once,
+    Loop,
+    PingPong,
+}
+
 #[derive(Clone)]
 pub struct Animation<T: Clone + PartialEq> {
     from: T,
@@ -31,6 +43,9 @@ pub struct Animation<T: Clone + PartialEq> {
     running: bool,
     transform_closure: fn(&T, &T, &mut T, f64),
     animation_curve: AnimationCurve,
+    repeat_mode: RepeatMode,
+    // keyframes played once the current one ends
+    keyframe_queue: VecDeque<(T, Duration, AnimationCurve)>,
 }
 
 impl<T: Clone + PartialEq> Animation<T> {
@@ -44,11 +59,42 @@ impl<T: Clone + PartialEq> Animation<T> {
             running: false,
             transform_closure,
             animation_curve: AnimationCurve::LINEAR,
+            repeat_mode: RepeatMode::Once,
+            keyframe_queue: VecDeque::new(),
         }
     }
 
     pub fn to(&mut self, to: T, duration: Duration, animation_curve: AnimationCurve) {
-        self.running = self.value != to;
+        self.keyframe_queue.clear();
+        self.start_keyframe(to, duration, animation_curve);
+        self.running = self.value != self.to;
+    }
+
+    // a repeating animation never ends, until it is replaced by another one
+    pub fn to_repeating(
+        &mut self,
+        to: T,
+        duration: Duration,
+        animation_curve: AnimationCurve,
+        repeat_mode: RepeatMode,
+    ) {
+        self.keyframe_queue.clear();
+        self.start_keyframe(to, duration, animation_curve);
+        self.repeat_mode = repeat_mode;
+    }
+
+    // keyframes are played back to back, a keyframe to the current value holds it for its duration
+    pub fn sequence(&mut self, keyframe_vec: Vec<(T, Duration, AnimationCurve)>) {
+        self.keyframe_queue = keyframe_vec.into();
+
+        if let Some((to, duration, animation_curve)) = self.keyframe_queue.pop_front() {
+            self.start_keyframe(to, duration, animation_curve);
+        }
+    }
+
+    fn start_keyframe(&mut self, to: T, duration: Duration, animation_curve: AnimationCurve) {
+        self.running = true;
+        self.repeat_mode = RepeatMode::Once;
         self.from = self.value.clone();
         self.to = to;
         self.start_time = Instant::now();
@@ -61,8 +107,27 @@ impl<T: Clone + PartialEq> Animation<T> {
             let elapsed = self.start_time.elapsed();
 
             if elapsed > self.duration {
-                self.value = self.to.clone();
-                self.running = false;
+                match self.repeat_mode {
+                    RepeatMode::Once => {
+                        self.value = self.to.clone();
+                        self.running = false;
+
+                        if let Some((to, duration, animation_curve)) =
+                            self.keyframe_queue.pop_front()
+                        {
+                            self.start_keyframe(to, duration, animation_curve);
+                        }
+                    }
+                    RepeatMode::Loop => {
+                        self.value = self.from.clone();
+                        self.start_time = Instant::now();
+                    }
+                    RepeatMode::PingPong => {
+                        std::mem::swap(&mut self.from, &mut self.to);
+                        self.value = self.from.clone();
+                        self.start_time = Instant::now();
+                    }
+                }
             } else {
                 let coef = (self.animation_curve.curve)(
                     elapsed.as_secs_f64() / self.duration.as_secs_f64(),
@@ -77,3 +142,96 @@ impl<T: Clone + PartialEq> Animation<T> {
         }
     }
 }
+
+fn is_affine(transform: &LayoutTransform) -> bool {
+    transform.m14 == 0.0 && transform.m24 == 0.0 && transform.m34 == 0.0 && transform.m44 == 1.0
+}
+
+// translation, rotation and scale of an affine transform, skew is dropped
+fn decompose_transform(
+    transform: &LayoutTransform,
+) -> (
+    LayoutVector3D,
+    Rotation3D<f32, LayoutPixel, LayoutPixel>,
+    LayoutVector3D,
+) {
+    let translation = LayoutVector3D::new(transform.m41, transform.m42, transform.m43);
+    let mut rows = [
+        LayoutVector3D::new(transform.m11, transform.m12, transform.m13),
+        LayoutVector3D::new(transform.m21, transform.m22, transform.m23),
+        LayoutVector3D::new(transform.m31, transform.m32, transform.m33),
+    ];
+    let mut scale = LayoutVector3D::new(rows[0].length(), rows[1].length(), rows[2].length());
+
+    // a mirrored transform is a negative scale on x
+    if rows[0].dot(rows[1].cross(rows[2])) < 0.0 {
+        scale.x = -scale.x;
+    }
+    for (row, scale) in rows.iter_mut().zip([scale.x, scale.y, scale.z]) {
+        if scale != 0.0 {
+            *row /= scale;
+        }
+    }
+
+    // the rows are the rotated axes, as built by Rotation3D::to_transform
+    let [[m11, m12, m13], [m21, m22, m23], [m31, m32, m33]] = rows.map(|row| row.to_array());
+    let trace = m11 + m22 + m33;
+    let (i, j, k, r) = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+
+        ((m23 - m32) / s, (m31 - m13) / s, (m12 - m21) / s, s / 4.0)
+    } else if m11 > m22 && m11 > m33 {
+        let s = (1.0 + m11 - m22 - m33).sqrt() * 2.0;
+
+        (s / 4.0, (m12 + m21) / s, (m13 + m31) / s, (m23 - m32) / s)
+    } else if m22 > m33 {
+        let s = (1.0 + m22 - m11 - m33).sqrt() * 2.0;
+
+        ((m12 + m21) / s, s / 4.0, (m23 + m32) / s, (m31 - m13) / s)
+    } else {
+        let s = (1.0 + m33 - m11 - m22).sqrt() * 2.0;
+
+        ((m13 + m31) / s, (m23 + m32) / s, s / 4.0, (m12 - m21) / s)
+    };
+
+    (
+        translation,
+        Rotation3D::quaternion(i, j, k, r).normalize(),
+        scale,
+    )
+}
+
+impl Animation<LayoutTransform> {
+    // translation and scale are interpolated linearly and rotation along the shortest arc,
+    // transforms with a perspective fall back to interpolating each matrix component
+    pub fn new_transform(value: LayoutTransform) -> Self {
+        Self::new(
+            value,
+            |from: &LayoutTransform,
+             to: &LayoutTransform,
+             value: &mut LayoutTransform,
+             coef: f64| {
+                let coef = coef as f32;
+
+                if is_affine(from) && is_affine(to) {
+                    let (from_translation, from_rotation, from_scale) = decompose_transform(from);
+                    let (to_translation, to_rotation, to_scale) = decompose_transform(to);
+                    let scale = from_scale.lerp(to_scale, coef);
+
+                    *value = LayoutTransform::scale(scale.x, scale.y, scale.z)
+                        .then(&from_rotation.slerp(&to_rotation, coef).to_transform())
+                        .then_translate(from_translation.lerp(to_translation, coef));
+                } else {
+                    let to = to.to_array();
+                    let mut array = from.to_array();
+
+                    for (component, to) in array.iter_mut().zip(to) {
+                        *component += (to - *component) * coef;
+                    }
+
+                    *value = LayoutTransform::from_array(array);
+                }
+            },
+        )
+    }
+}
@@ -1,17 +1,27 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::window::GlobalStateTrait;
 use crate::{ConnectionEvent, Driver, GlobalState};
 
+use tokio::task::JoinHandle;
 use tokio::{spawn, time};
 use util::connection::command::{CommandTrait, Commands, RequestDeviceConfig};
 use util::connection::{ConnectionState, Server};
 use util::thread::MutexTrait;
 
+// framing cap of the driver connection, bigger packets are dropped by the receiver
+pub const MAX_PACKET_SIZE: usize = 20_000_000;
+
 pub struct Connection {
     server: Server,
     global_state: Arc<GlobalState>,
+    // asks the send task to flush what is queued and stop
+    do_stop: Arc<AtomicBool>,
+    send_task_handle_option: Option<JoinHandle<()>>,
+    receive_task_handle_option: Option<JoinHandle<()>>,
 }
 
 impl Connection {
@@ -21,60 +31,103 @@ impl Connection {
         Self {
             server,
             global_state,
+            do_stop: Arc::new(AtomicBool::new(false)),
+            send_task_handle_option: None,
+            receive_task_handle_option: None,
         }
     }
 
-    pub async fn run(&self) {
+    pub async fn run(&mut self) {
         {
             let global_state = self.global_state.clone();
             let server_dualchannel = self.server.dual_channel.clone();
+            let do_stop = self.do_stop.clone();
 
-            spawn(async move {
+            self.send_task_handle_option = Some(spawn(async move {
                 let mut interval = time::interval(Duration::from_millis(100));
+                let apply_debounce = Duration::from_millis(
+                    global_state
+                        .settings_manager_mutex
+                        .lock_poisoned()
+                        .config
+                        .apply_debounce_ms,
+                );
 
                 loop {
+                    // read before sending, so the events queued until then are all sent
+                    let do_stop = do_stop.load(Ordering::Relaxed);
+
                     // send data to clients
                     {
-                        if let Some(connection_event) = global_state.pop_connection_event() {
+                        let mut packet_vec: Vec<(SocketAddr, Vec<u8>)> = vec![];
+
+                        while let Some(connection_event) = global_state.pop_connection_event() {
                             match connection_event {
                                 ConnectionEvent::RequestDeviceConfig(device_id) => {
-                                    server_dualchannel
-                                        .send_async((
+                                    // a pending apply goes first, or the old config would be loaded
+                                    if let Some(mut device_config) =
+                                        global_state.take_pending_apply(&device_id)
+                                    {
+                                        packet_vec.push((
                                             device_id.socket_addr,
-                                            ConnectionState::Data(
-                                                RequestDeviceConfig::new(device_id.serial_number)
-                                                    .to_bytes(),
-                                            ),
-                                        ))
-                                        .await
-                                        .ok();
+                                            device_config.to_bytes(),
+                                        ));
+                                    }
+
+                                    packet_vec.push((
+                                        device_id.socket_addr,
+                                        RequestDeviceConfig::new(device_id.serial_number)
+                                            .to_bytes(),
+                                    ));
                                 }
-                                ConnectionEvent::ApplyDeviceConfig(
-                                    socket_addr,
-                                    mut device_config,
-                                ) => {
-                                    server_dualchannel
-                                        .send_async((
-                                            socket_addr,
-                                            ConnectionState::Data(device_config.to_bytes()),
-                                        ))
-                                        .await
-                                        .ok();
+                                ConnectionEvent::ApplyDeviceConfig(socket_addr, device_config) => {
+                                    global_state.push_pending_apply(socket_addr, device_config);
                                 }
                             }
                         }
+
+                        // everything still pending is sent when stopping
+                        for (socket_addr, mut device_config) in global_state
+                            .take_ready_pending_applies(if do_stop {
+                                Duration::ZERO
+                            } else {
+                                apply_debounce
+                            })
+                        {
+                            packet_vec.push((socket_addr, device_config.to_bytes()));
+                        }
+
+                        for (socket_addr, data) in packet_vec {
+                            // the driver may have disconnected in the meantime
+                            if !global_state
+                                .driver_hashmap_mutex
+                                .lock_poisoned()
+                                .contains_key(&socket_addr)
+                            {
+                                continue;
+                            }
+
+                            server_dualchannel
+                                .send_async((socket_addr, ConnectionState::Data(data)))
+                                .await
+                                .ok();
+                        }
+                    }
+
+                    if do_stop {
+                        break;
                     }
 
                     interval.tick().await;
                 }
-            });
+            }));
         }
 
         {
             let global_state = self.global_state.clone();
             let server_dualchannel = self.server.dual_channel.clone();
 
-            spawn(async move {
+            self.receive_task_handle_option = Some(spawn(async move {
                 loop {
                     // receive data from clients
                     if let Ok((socket_addr, connection_state)) =
@@ -83,7 +136,9 @@ impl Connection {
                         let mut driver_hashmap = global_state.driver_hashmap_mutex.lock_poisoned();
 
                         match connection_state {
-                            ConnectionState::Start => {}
+                            ConnectionState::Start => {
+                                global_state.clear_connection_event(socket_addr);
+                            }
                             ConnectionState::Data(data) => {
                                 if data.len() > 0 {
                                     match Commands::from(data) {
@@ -100,7 +155,7 @@ impl Connection {
                                             if let Some(driver) =
                                                 driver_hashmap.get_mut(&socket_addr)
                                             {
-                                                driver.device_list = device_list;
+                                                driver.update_device_list(device_list);
                                             }
 
                                             global_state.request_redraw();
@@ -110,6 +165,10 @@ impl Connection {
                                                 .selected_device_id_option_mutex
                                                 .lock_poisoned()
                                                 .as_ref()
+                                                // ignore configs from another driver
+                                                .filter(|selected_device_id| {
+                                                    selected_device_id.socket_addr == socket_addr
+                                                })
                                             {
                                                 let mut selected_device_config_option =
                                                     global_state
@@ -154,12 +213,26 @@ impl Connection {
                             ConnectionState::End => {
                                 // clearing driver data
                                 driver_hashmap.remove(&socket_addr);
+                                global_state.clear_connection_event(socket_addr);
                                 global_state.request_redraw();
                             }
                         }
                     }
                 }
-            });
+            }));
+        }
+    }
+
+    // the send task sends what is still queued before stopping, nothing is received anymore,
+    // closing the sockets with the end frame needs a stop handle on the util server
+    pub async fn shutdown(&mut self) {
+        self.do_stop.store(true, Ordering::Relaxed);
+
+        if let Some(send_task_handle) = self.send_task_handle_option.take() {
+            send_task_handle.await.ok();
+        }
+        if let Some(receive_task_handle) = self.receive_task_handle_option.take() {
+            receive_task_handle.abort();
         }
     }
 }
@@ -3,19 +3,27 @@
 
 mod animation;
 mod connection;
+mod profile;
 mod ui;
 mod window;
 
 use std::collections::VecDeque;
+use std::env;
 use std::net::SocketAddr;
-use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicBool, AtomicU8};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use connection::Connection;
 use ui::{App, DocumentTrait};
 
+use hashbrown::hash_map::Entry;
 use hashbrown::HashMap;
+use log::error;
+use rfd::{MessageButtons, MessageDialog, MessageLevel};
+use serde::{Deserialize, Serialize};
+use util::config::ConfigManager;
 use util::connection::command::DeviceConfig;
 use util::thread::MutexTrait;
 use util::{
@@ -32,6 +40,10 @@ use winit::dpi::PhysicalSize;
 pub struct Driver {
     driver_configuration_descriptor: DriverConfigurationDescriptor,
     device_list: DeviceList,
+    // tells apart two connections from the same address
+    connection_instant: Instant,
+    // when each device was first reported, a device reported again later is a new connection
+    device_connection_instant_hashmap: HashMap<String, Instant>,
 }
 
 impl Driver {
@@ -39,10 +51,29 @@ impl Driver {
         Self {
             driver_configuration_descriptor,
             device_list: DeviceList::default(),
+            connection_instant: Instant::now(),
+            device_connection_instant_hashmap: HashMap::new(),
         }
     }
+
+    fn update_device_list(&mut self, device_list: DeviceList) {
+        let now = Instant::now();
+
+        self.device_connection_instant_hashmap
+            .retain(|serial_number, _| device_list.serial_number_vec.contains(serial_number));
+
+        for serial_number in device_list.serial_number_vec.iter() {
+            self.device_connection_instant_hashmap
+                .entry(serial_number.clone())
+                .or_insert(now);
+        }
+
+        self.device_list = device_list;
+    }
 }
 
+// a device is identified by its driver connection and its serial number,
+// so two drivers reporting the same serial number never share a config
 #[derive(Clone, PartialEq, Debug)]
 pub struct DeviceId {
     socket_addr: SocketAddr,
@@ -58,32 +89,106 @@ impl DeviceId {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum CloseBehavior {
+    Quit,
+    // there is no tray icon yet to restore a hidden window from, so it goes to the taskbar
+    Minimize,
+}
+
+impl Default for CloseBehavior {
+    fn default() -> Self {
+        Self::Quit
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    close_behavior: CloseBehavior,
+    line_height: f32,
+    scroll_speed: f32,
+    shift_horizontal_scroll: bool,
+    multisampling: u16,
+    fallback_background_opacity: u8,
+    // only the last config of a burst of applies is sent, at most this long after the first apply
+    apply_debounce_ms: u64,
+    // cross-fade between documents, instant switches when disabled
+    document_transition: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            close_behavior: CloseBehavior::default(),
+            line_height: 21.0,
+            scroll_speed: 1.0,
+            shift_horizontal_scroll: true,
+            multisampling: 4,
+            fallback_background_opacity: 255,
+            apply_debounce_ms: 200,
+            document_transition: true,
+        }
+    }
+}
+
+// what was opened when the gui was closed, restored on launch
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct UiState {
+    last_serial_number: Option<String>,
+    last_mode: usize,
+}
+
 enum ConnectionEvent {
     RequestDeviceConfig(DeviceId),
     ApplyDeviceConfig(SocketAddr, DeviceConfig),
 }
 
+impl ConnectionEvent {
+    fn socket_addr(&self) -> SocketAddr {
+        match self {
+            ConnectionEvent::RequestDeviceConfig(device_id) => device_id.socket_addr,
+            ConnectionEvent::ApplyDeviceConfig(socket_addr, _) => *socket_addr,
+        }
+    }
+}
+
+// keeps the queued events of the other connections, in order
+fn remove_connection_events(event_queue: &mut VecDeque<ConnectionEvent>, socket_addr: SocketAddr) {
+    event_queue.retain(|event| event.socket_addr() != socket_addr);
+}
+
 pub struct GlobalState {
+    settings_manager_mutex: Mutex<ConfigManager<Settings>>,
+    ui_state_manager_mutex: Mutex<ConfigManager<UiState>>,
     font_hashmap_mutex: Mutex<HashMap<&'static str, Font>>,
     do_redraw: AtomicBool,
+    background_alpha: AtomicU8,
     driver_hashmap_mutex: Mutex<HashMap<SocketAddr, Driver>>,
     device_id_vec_mutex: Mutex<Vec<DeviceId>>,
     selected_device_id_option_mutex: Mutex<Option<DeviceId>>,
     selected_device_config_option_mutex: Mutex<Option<DeviceConfig>>,
     connection_event_queue_mutex: Mutex<VecDeque<ConnectionEvent>>,
+    // latest config to apply of each device, with the time its first apply was queued
+    pending_apply_hashmap_mutex: Mutex<HashMap<(SocketAddr, String), (DeviceConfig, Instant)>>,
     new_document_option_mutex: Mutex<Option<Box<dyn DocumentTrait + Send>>>,
 }
 
 impl GlobalState {
     fn new() -> Arc<Self> {
         Arc::new(Self {
+            settings_manager_mutex: Mutex::new(ConfigManager::new("gui_settings")),
+            ui_state_manager_mutex: Mutex::new(ConfigManager::new("gui_state")),
             font_hashmap_mutex: Mutex::new(HashMap::new()),
             do_redraw: AtomicBool::new(true),
+            background_alpha: AtomicU8::new(240),
             driver_hashmap_mutex: Mutex::new(HashMap::new()),
             device_id_vec_mutex: Mutex::new(vec![]),
             selected_device_id_option_mutex: Mutex::new(None),
             selected_device_config_option_mutex: Mutex::new(None),
             connection_event_queue_mutex: Mutex::new(VecDeque::new()),
+            pending_apply_hashmap_mutex: Mutex::new(HashMap::new()),
             new_document_option_mutex: Mutex::new(None),
         })
     }
@@ -99,6 +204,66 @@ impl GlobalState {
             .lock_poisoned()
             .pop_front()
     }
+
+    // a newer config replaces the pending one but keeps its time,
+    // so a burst of applies is sent at most one debounce after the first one
+    fn push_pending_apply(&self, socket_addr: SocketAddr, device_config: DeviceConfig) {
+        let mut pending_apply_hashmap = self.pending_apply_hashmap_mutex.lock_poisoned();
+
+        match pending_apply_hashmap.entry((socket_addr, device_config.serial_number.clone())) {
+            Entry::Occupied(mut entry) => entry.get_mut().0 = device_config,
+            Entry::Vacant(entry) => {
+                entry.insert((device_config, Instant::now()));
+            }
+        }
+    }
+
+    fn take_pending_apply(&self, device_id: &DeviceId) -> Option<DeviceConfig> {
+        self.pending_apply_hashmap_mutex
+            .lock_poisoned()
+            .remove(&(device_id.socket_addr, device_id.serial_number.clone()))
+            .map(|(device_config, _)| device_config)
+    }
+
+    fn take_ready_pending_applies(&self, debounce: Duration) -> Vec<(SocketAddr, DeviceConfig)> {
+        self.pending_apply_hashmap_mutex
+            .lock_poisoned()
+            .drain_filter(|_, (_, instant)| instant.elapsed() >= debounce)
+            .map(|((socket_addr, _), (device_config, _))| (socket_addr, device_config))
+            .collect()
+    }
+
+    // the last mode belongs to the last device, another device starts on the first mode
+    fn set_last_serial_number(&self, last_serial_number: Option<String>) {
+        let mut ui_state_manager = self.ui_state_manager_mutex.lock_poisoned();
+
+        if ui_state_manager.config.last_serial_number != last_serial_number {
+            ui_state_manager.config.last_serial_number = last_serial_number;
+            ui_state_manager.config.last_mode = 0;
+            ui_state_manager.save();
+        }
+    }
+
+    fn set_last_mode(&self, last_mode: usize) {
+        let mut ui_state_manager = self.ui_state_manager_mutex.lock_poisoned();
+
+        if ui_state_manager.config.last_mode != last_mode {
+            ui_state_manager.config.last_mode = last_mode;
+            ui_state_manager.save();
+        }
+    }
+
+    // discard the queued events and pending applies of a connection,
+    // they must not reach a later connection
+    fn clear_connection_event(&self, socket_addr: SocketAddr) {
+        remove_connection_events(
+            &mut self.connection_event_queue_mutex.lock_poisoned(),
+            socket_addr,
+        );
+        self.pending_apply_hashmap_mutex
+            .lock_poisoned()
+            .retain(|(pending_socket_addr, _), _| *pending_socket_addr != socket_addr);
+    }
 }
 
 impl GlobalStateTrait for GlobalState {
@@ -111,17 +276,25 @@ impl GlobalStateTrait for GlobalState {
     }
 }
 
+// MAD_RUST_LOG takes precedence over RUST_LOG, e.g. MAD_RUST_LOG=mad_rust=debug,webrender=warn
+fn init_logger() {
+    let filter = env::var("MAD_RUST_LOG")
+        .or_else(|_| env::var("RUST_LOG"))
+        .unwrap_or_else(|_| "warn".to_string());
+
+    env_logger::Builder::new().parse_filters(&filter).init();
+}
+
 #[tokio::main]
 async fn main() {
-    #[cfg(not(target_os = "windows"))]
-    sudo::escalate_if_needed().unwrap();
+    init_logger();
 
     if kill_double() {
         return;
     }
 
     let global_state = GlobalState::new();
-    let connection = Connection::new(global_state.clone()).await;
+    let mut connection = Connection::new(global_state.clone()).await;
 
     connection.run().await;
 
@@ -132,19 +305,58 @@ async fn main() {
     window_options.decorations = false;
     window_options.min_size = Some(PhysicalSize::new(533, 300));
 
-    let mut window = Window::new(window_options, global_state);
+    {
+        let settings_manager = global_state.settings_manager_mutex.lock_poisoned();
+
+        window_options.line_height = settings_manager.config.line_height;
+        window_options.scroll_speed = settings_manager.config.scroll_speed;
+        window_options.shift_horizontal_scroll = settings_manager.config.shift_horizontal_scroll;
+        window_options.multisampling = settings_manager.config.multisampling;
+    }
+
+    let mut window = match Window::new(window_options, global_state) {
+        Ok(window) => window,
+        Err(error) => {
+            error!("{}", error);
+            MessageDialog::new()
+                .set_level(MessageLevel::Error)
+                .set_title("Mad rust")
+                .set_description(&error)
+                .set_buttons(MessageButtons::Ok)
+                .show();
+            connection.shutdown().await;
+            return;
+        }
+    };
 
     {
         // add background blur effect on windows and macos
         #[cfg(target_os = "windows")]
-        apply_blur(&window.wrapper.context.window(), None).ok();
+        let background_effect_applied = apply_blur(&window.wrapper.context.window(), None).is_ok();
 
         #[cfg(target_os = "macos")]
-        apply_vibrancy(
-            &window.context.window(),
+        let background_effect_applied = apply_vibrancy(
+            &window.wrapper.context.window(),
             NSVisualEffectMaterial::AppearanceBased,
         )
-        .ok();
+        .is_ok();
+
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        let background_effect_applied = false;
+
+        // without blur the translucent background is hard to read
+        if !background_effect_applied {
+            let global_state = &window.wrapper.global_state;
+
+            global_state.background_alpha.store(
+                global_state
+                    .settings_manager_mutex
+                    .lock_poisoned()
+                    .config
+                    .fallback_background_opacity,
+                Ordering::Relaxed,
+            );
+        }
     }
 
     window
@@ -153,4 +365,42 @@ async fn main() {
     window.set_window::<App>();
     window.run();
     window.deinit();
+    connection.shutdown().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remove_connection_events_keeps_other_connections() {
+        let socket_addr: SocketAddr = "127.0.0.1:1000".parse().unwrap();
+        let other_socket_addr: SocketAddr = "127.0.0.1:2000".parse().unwrap();
+        let mut event_queue: VecDeque<ConnectionEvent> = [
+            (socket_addr, "A"),
+            (other_socket_addr, "B"),
+            (socket_addr, "C"),
+            (other_socket_addr, "D"),
+        ]
+        .into_iter()
+        .map(|(socket_addr, serial_number)| {
+            ConnectionEvent::RequestDeviceConfig(DeviceId::new(
+                socket_addr,
+                serial_number.to_string(),
+            ))
+        })
+        .collect();
+
+        remove_connection_events(&mut event_queue, socket_addr);
+
+        let serial_number_vec: Vec<&str> = event_queue
+            .iter()
+            .map(|event| match event {
+                ConnectionEvent::RequestDeviceConfig(device_id) => device_id.serial_number.as_str(),
+                ConnectionEvent::ApplyDeviceConfig(..) => unreachable!(),
+            })
+            .collect();
+
+        assert_eq!(serial_number_vec, vec!["B", "D"]);
+    }
 }
@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use util::connection::command::DriverConfigurationDescriptor;
+
+// a device config shared as a json file, the serial number is not kept
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Profile {
+    pub device_name: String,
+    pub config: Vec<[Vec<String>; 2]>,
+}
+
+impl Profile {
+    pub fn import(path: &Path) -> Result<Self, String> {
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            return Err("Only json profiles can be imported".to_string());
+        }
+
+        let data = fs::read_to_string(path)
+            .map_err(|error| format!("Failed to read the profile : {}", error))?;
+
+        serde_json::from_str(&data).map_err(|error| format!("Invalid profile : {}", error))
+    }
+
+    pub fn export(&self, path: &Path) -> Result<(), String> {
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|error| format!("Failed to serialize the profile : {}", error))?;
+
+        fs::write(path, data).map_err(|error| format!("Failed to write the profile : {}", error))
+    }
+
+    // check that the profile matches the buttons and modes of the driver
+    pub fn validate(
+        &self,
+        driver_configuration_descriptor: &DriverConfigurationDescriptor,
+    ) -> Result<(), String> {
+        if self.device_name != driver_configuration_descriptor.device_name {
+            return Err(format!(
+                "This profile is made for a {} device",
+                self.device_name
+            ));
+        }
+        if self.config.len() != driver_configuration_descriptor.button_name_vec.len() {
+            return Err(format!(
+                "Invalid profile : {} buttons expected, found {}",
+                driver_configuration_descriptor.button_name_vec.len(),
+                self.config.len()
+            ));
+        }
+
+        for config in self.config.iter() {
+            if config[0].len() != driver_configuration_descriptor.mode_count as usize
+                || config[1].len() != driver_configuration_descriptor.shift_mode_count as usize
+            {
+                return Err(format!(
+                    "Invalid profile : {} modes and {} shift modes expected",
+                    driver_configuration_descriptor.mode_count,
+                    driver_configuration_descriptor.shift_mode_count
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use crate::ui::App;
+use crate::window::ext::{ColorFTrait, DisplayListBuilderExt};
+use crate::window::{FrameBuilder, WindowWrapper};
+use crate::GlobalState;
+
+use util::thread::MutexTrait;
+use webrender::api::units::{LayoutPoint, LayoutRect, LayoutSize};
+use webrender::api::{BorderRadius, ClipMode, ColorF, CommonItemProperties};
+
+impl App {
+    // the overlay text is rebuilt a few times per second, not every frame
+    pub fn animate_frame_stats(&mut self, wrapper: &WindowWrapper<GlobalState>) {
+        if wrapper.frame_stats.is_enabled() && self.frame_stats_timer.check() {
+            wrapper.global_state.request_redraw();
+        }
+    }
+
+    // frame statistics overlay in the bottom left corner, toggled with f12,
+    // its own refresh counts as display list builds too
+    pub fn draw_frame_stats(
+        &self,
+        frame_builder: &mut FrameBuilder,
+        wrapper: &WindowWrapper<GlobalState>,
+    ) {
+        if !wrapper.frame_stats.is_enabled() {
+            return;
+        }
+
+        let text = wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"]
+            .create_text(
+                format!(
+                    "{:.0} fps | loop {:.2} ms | build {:.2} ms | {} builds/s",
+                    wrapper.frame_stats.fps(),
+                    wrapper.frame_stats.loop_time().as_secs_f32() * 1000.0,
+                    wrapper.frame_stats.build_time().as_secs_f32() * 1000.0,
+                    wrapper.frame_stats.builds_per_second(),
+                ),
+                None,
+            );
+        let layout_rect = LayoutRect::from_origin_and_size(
+            LayoutPoint::new(10.0, wrapper.window_size.height as f32 - 35.0),
+            LayoutSize::new(text.size.width + 20.0, 25.0),
+        );
+
+        frame_builder.builder.push_rounded_rect(
+            &CommonItemProperties::new(layout_rect, frame_builder.space_and_clip),
+            ColorF::new_u(0, 0, 0, 200),
+            BorderRadius::uniform(3.0),
+            ClipMode::Clip,
+        );
+        text.push_text(
+            &mut frame_builder.builder,
+            frame_builder.space_and_clip,
+            layout_rect.min + LayoutSize::new(10.0, 4.0),
+            ColorF::WHITE,
+            None,
+        );
+    }
+}
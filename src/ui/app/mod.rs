@@ -1,2 +1,3 @@
+pub mod frame_stats;
 pub mod title_bar;
 pub mod window_resize;
@@ -2,7 +2,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use crate::animation::AnimationCurve;
-use crate::ui::{App, AppEvent};
+use crate::ui::{App, AppEvent, HitTag};
 use crate::window::ext::{ColorFTrait, DisplayListBuilderExt};
 use crate::window::FrameBuilder;
 use crate::GlobalState;
@@ -22,13 +22,26 @@ use webrender::Transaction;
 use winit::dpi::PhysicalSize;
 
 impl App {
-    pub fn update_title_bar_over_state(&mut self, new_over_state: &HashSet<(AppEvent, u16)>) {
-        if new_over_state.contains(&(AppEvent::CloseButton, 0)) {
-            self.close_button_color_animation.to(
-                ColorF::new_u(255, 79, 0, 150),
-                Duration::from_millis(100),
-                AnimationCurve::EASE_OUT,
-            );
+    pub fn update_title_bar_over_state(&mut self, new_over_state: &HashSet<(AppEvent, HitTag)>) {
+        if new_over_state.contains(&(AppEvent::CloseButton, HitTag::None)) {
+            // flash before settling on the hover color, only when the button is entered
+            if !self
+                .over_states
+                .contains(&(AppEvent::CloseButton, HitTag::None))
+            {
+                self.close_button_color_animation.sequence(vec![
+                    (
+                        ColorF::new_u(255, 79, 0, 200),
+                        Duration::from_millis(80),
+                        AnimationCurve::EASE_OUT,
+                    ),
+                    (
+                        ColorF::new_u(255, 79, 0, 150),
+                        Duration::from_millis(120),
+                        AnimationCurve::EASE_IN_OUT,
+                    ),
+                ]);
+            }
         } else {
             self.close_button_color_animation.to(
                 ColorF::new_u(255, 79, 0, 100),
@@ -36,7 +49,7 @@ impl App {
                 AnimationCurve::EASE_IN,
             );
         }
-        if new_over_state.contains(&(AppEvent::MaximizeButton, 0)) {
+        if new_over_state.contains(&(AppEvent::MaximizeButton, HitTag::None)) {
             self.maximize_button_color_animation.to(
                 ColorF::new_u(255, 189, 0, 150),
                 Duration::from_millis(100),
@@ -49,7 +62,7 @@ impl App {
                 AnimationCurve::EASE_IN,
             );
         }
-        if new_over_state.contains(&(AppEvent::MinimizeButton, 0)) {
+        if new_over_state.contains(&(AppEvent::MinimizeButton, HitTag::None)) {
             self.minimize_button_color_animation.to(
                 ColorF::new_u(50, 221, 23, 150),
                 Duration::from_millis(100),
@@ -62,7 +75,7 @@ impl App {
                 AnimationCurve::EASE_IN,
             );
         }
-        if new_over_state.contains(&(AppEvent::ReturnButton, 0)) {
+        if new_over_state.contains(&(AppEvent::ReturnButton, HitTag::None)) {
             self.return_button_color_animation.to(
                 ColorF::new_u(33, 33, 33, 100),
                 Duration::from_millis(100),
@@ -145,7 +158,7 @@ impl App {
             frame_builder.space_and_clip.clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::TitleBar.into(), 0),
+            AppEvent::TitleBar.tag(HitTag::None),
         );
 
         // return button
@@ -171,7 +184,7 @@ impl App {
                 frame_builder.space_and_clip.clip_chain_id,
                 frame_builder.space_and_clip.spatial_id,
                 PrimitiveFlags::empty(),
-                (AppEvent::ReturnButton.into(), 0),
+                AppEvent::ReturnButton.tag(HitTag::None),
             );
 
             // arrow
@@ -256,7 +269,7 @@ impl App {
             frame_builder.space_and_clip.clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::CloseButton.into(), 0),
+            AppEvent::CloseButton.tag(HitTag::None),
         );
 
         // maximize button
@@ -281,7 +294,7 @@ impl App {
             frame_builder.space_and_clip.clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::MaximizeButton.into(), 0),
+            AppEvent::MaximizeButton.tag(HitTag::None),
         );
 
         // minimize button
@@ -306,7 +319,7 @@ impl App {
             frame_builder.space_and_clip.clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::MinimizeButton.into(), 0),
+            AppEvent::MinimizeButton.tag(HitTag::None),
         );
     }
 }
@@ -1,22 +1,57 @@
-use crate::ui::{App, AppEvent};
-use crate::window::{FrameBuilder, WindowWrapper};
+use crate::ui::{App, AppEvent, HitTag};
+use crate::window::ext::ColorFTrait;
+use crate::window::{FrameBuilder, GlobalStateTrait, WindowWrapper};
 use crate::GlobalState;
 
 use hashbrown::HashSet;
-use webrender::api::units::{LayoutPoint, LayoutRect, LayoutSize};
-use webrender::api::{BorderRadius, ClipMode, ComplexClipRegion, PrimitiveFlags};
+use webrender::api::units::{LayoutPoint, LayoutRect, LayoutSideOffsets, LayoutSize};
+use webrender::api::{
+    BorderDetails, BorderRadius, BorderSide, BorderStyle, ClipMode, ColorF, CommonItemProperties,
+    ComplexClipRegion, NormalBorder, PrimitiveFlags,
+};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ModifiersState, VirtualKeyCode};
 use winit::window::CursorIcon;
 
+// pixels moved or resized by each arrow key press
+const KEYBOARD_RESIZE_STEP: i32 = 10;
+
+// bounds of the monitor under the point, winit does not expose their work areas
+fn monitor_bounds(
+    wrapper: &WindowWrapper<GlobalState>,
+    point: PhysicalPosition<f64>,
+) -> Option<(PhysicalPosition<f64>, PhysicalPosition<f64>)> {
+    wrapper
+        .context
+        .window()
+        .available_monitors()
+        .map(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+
+            (
+                PhysicalPosition::new(position.x as f64, position.y as f64),
+                PhysicalPosition::new(
+                    position.x as f64 + size.width as f64,
+                    position.y as f64 + size.height as f64,
+                ),
+            )
+        })
+        .find(|(min_position, max_position)| {
+            (min_position.x..=max_position.x).contains(&point.x)
+                && (min_position.y..=max_position.y).contains(&point.y)
+        })
+}
+
 impl App {
     pub fn update_window_resize_cursor_icon(
         &self,
-        new_over_state: &HashSet<(AppEvent, u16)>,
+        new_over_state: &HashSet<(AppEvent, HitTag)>,
         wrapper: &mut WindowWrapper<GlobalState>,
     ) {
         if let None = self.resizing {
             let test_cursor = |event: AppEvent, cursor: CursorIcon| -> bool {
-                if new_over_state.contains(&(event, 0)) {
+                if new_over_state.contains(&(event, HitTag::None)) {
                     wrapper.context.window().set_cursor_icon(cursor);
 
                     true
@@ -49,12 +84,62 @@ impl App {
         wrapper: &mut WindowWrapper<GlobalState>,
     ) {
         if let Some(event) = self.resizing.clone() {
+            // macos reports the motion in points, the window geometry is in pixels,
+            // the other platforms report raw device units, used as pixels
+            #[cfg(target_os = "macos")]
+            let delta = {
+                let scale_factor = wrapper.context.window().scale_factor();
+
+                PhysicalPosition::new(delta.x * scale_factor, delta.y * scale_factor)
+            };
             let window_size = wrapper.get_window_size();
             let window_position = wrapper.get_window_position();
+            let move_left = matches!(
+                event,
+                AppEvent::WindowResizeTopLeft
+                    | AppEvent::WindowResizeBottomLeft
+                    | AppEvent::WindowResizeLeft
+            );
+            let move_right = matches!(
+                event,
+                AppEvent::WindowResizeTopRight
+                    | AppEvent::WindowResizeBottomRight
+                    | AppEvent::WindowResizeRight
+            );
+            let move_top = matches!(
+                event,
+                AppEvent::WindowResizeTopLeft
+                    | AppEvent::WindowResizeTopRight
+                    | AppEvent::WindowResizeTop
+            );
+            let move_bottom = matches!(
+                event,
+                AppEvent::WindowResizeBottomLeft
+                    | AppEvent::WindowResizeBottomRight
+                    | AppEvent::WindowResizeBottom
+            );
+            // middle of the grabbed edge, or the grabbed corner
+            let grabbed_point = |position: PhysicalPosition<f64>, size: PhysicalSize<f64>| {
+                PhysicalPosition::new(
+                    position.x
+                        + match (move_left, move_right) {
+                            (true, _) => 0.0,
+                            (_, true) => size.width,
+                            _ => size.width / 2.0,
+                        },
+                    position.y
+                        + match (move_top, move_bottom) {
+                            (true, _) => 0.0,
+                            (_, true) => size.height,
+                            _ => size.height / 2.0,
+                        },
+                )
+            };
             let mut new_window_size =
                 PhysicalSize::new(window_size.width as f64, window_size.height as f64);
             let mut new_window_position =
                 PhysicalPosition::new(window_position.x as f64, window_position.y as f64);
+            let previous_grabbed_point = grabbed_point(new_window_position, new_window_size);
 
             match event {
                 AppEvent::WindowResizeTopLeft => {
@@ -90,6 +175,39 @@ impl App {
                 _ => {}
             }
 
+            // a grabbed edge leaving every monitor is kept on the one it was on,
+            // so the window can't be dragged out of reach, the other edges are left where they are
+            let monitor_bounds_option = match monitor_bounds(
+                wrapper,
+                grabbed_point(new_window_position, new_window_size),
+            ) {
+                Some(_) => None,
+                None => monitor_bounds(wrapper, previous_grabbed_point),
+            };
+
+            if let Some((min_position, max_position)) = monitor_bounds_option {
+                let mut left = new_window_position.x;
+                let mut top = new_window_position.y;
+                let mut right = new_window_position.x + new_window_size.width;
+                let mut bottom = new_window_position.y + new_window_size.height;
+
+                if move_left {
+                    left = left.max(min_position.x);
+                }
+                if move_right {
+                    right = right.min(max_position.x);
+                }
+                if move_top {
+                    top = top.max(min_position.y);
+                }
+                if move_bottom {
+                    bottom = bottom.min(max_position.y);
+                }
+
+                new_window_position = PhysicalPosition::new(left, top);
+                new_window_size = PhysicalSize::new(right - left, bottom - top);
+            }
+
             wrapper.set_window_size(PhysicalSize::new(
                 new_window_size.width as u32,
                 new_window_size.height as u32,
@@ -101,6 +219,58 @@ impl App {
         }
     }
 
+    // alt + f8 toggles the mode, arrows move the window, shift + arrows resize it,
+    // escape or enter leaves the mode, returns true when the key is consumed
+    pub fn update_keyboard_resize(
+        &mut self,
+        keycode: VirtualKeyCode,
+        modifiers: ModifiersState,
+        wrapper: &mut WindowWrapper<GlobalState>,
+    ) -> bool {
+        if keycode == VirtualKeyCode::F8 && modifiers.alt() {
+            self.keyboard_resizing = !self.keyboard_resizing;
+            wrapper.global_state.request_redraw();
+
+            return true;
+        }
+        if !self.keyboard_resizing {
+            return false;
+        }
+
+        let (delta_x, delta_y) = match keycode {
+            VirtualKeyCode::Left => (-KEYBOARD_RESIZE_STEP, 0),
+            VirtualKeyCode::Right => (KEYBOARD_RESIZE_STEP, 0),
+            VirtualKeyCode::Up => (0, -KEYBOARD_RESIZE_STEP),
+            VirtualKeyCode::Down => (0, KEYBOARD_RESIZE_STEP),
+            VirtualKeyCode::Escape | VirtualKeyCode::Return => {
+                self.keyboard_resizing = false;
+                wrapper.global_state.request_redraw();
+
+                return true;
+            }
+            _ => return true,
+        };
+
+        if modifiers.shift() {
+            let window_size = wrapper.get_window_size();
+
+            // clamped to the min and max size by set_window_size
+            wrapper.set_window_size(PhysicalSize::new(
+                (window_size.width as i32 + delta_x).max(0) as u32,
+                (window_size.height as i32 + delta_y).max(0) as u32,
+            ));
+        } else {
+            let window_position = wrapper.get_window_position();
+
+            wrapper.set_window_position(PhysicalPosition::new(
+                window_position.x + delta_x,
+                window_position.y + delta_y,
+            ));
+        }
+
+        true
+    }
+
     pub fn draw_window_resize(
         &mut self,
         window_size: PhysicalSize<u32>,
@@ -123,6 +293,32 @@ impl App {
         );
         let clip_chain_id = builder.define_clip_chain(None, [clip_id]);
 
+        // show that the window is moved or resized with the keyboard
+        if self.keyboard_resizing {
+            let window_layout_rect = LayoutRect::from_size(LayoutSize::new(
+                window_size.width as f32,
+                window_size.height as f32,
+            ));
+            let border_side = BorderSide {
+                color: ColorF::new_u(52, 152, 219, 255),
+                style: BorderStyle::Solid,
+            };
+
+            builder.push_border(
+                &CommonItemProperties::new(window_layout_rect, frame_builder.space_and_clip),
+                window_layout_rect,
+                LayoutSideOffsets::new_all_same(2.0),
+                BorderDetails::Normal(NormalBorder {
+                    left: border_side,
+                    right: border_side,
+                    top: border_side,
+                    bottom: border_side,
+                    radius: BorderRadius::uniform(5.0),
+                    do_aa: true,
+                }),
+            );
+        }
+
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
                 LayoutPoint::new(20.0, 0.0),
@@ -131,7 +327,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeTop.into(), 0),
+            AppEvent::WindowResizeTop.tag(HitTag::None),
         );
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
@@ -141,7 +337,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeBottom.into(), 0),
+            AppEvent::WindowResizeBottom.tag(HitTag::None),
         );
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
@@ -151,7 +347,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeLeft.into(), 0),
+            AppEvent::WindowResizeLeft.tag(HitTag::None),
         );
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
@@ -161,7 +357,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeRight.into(), 0),
+            AppEvent::WindowResizeRight.tag(HitTag::None),
         );
 
         // corners
@@ -173,7 +369,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeTopLeft.into(), 0),
+            AppEvent::WindowResizeTopLeft.tag(HitTag::None),
         );
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
@@ -183,7 +379,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeTopRight.into(), 0),
+            AppEvent::WindowResizeTopRight.tag(HitTag::None),
         );
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
@@ -193,7 +389,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeBottomLeft.into(), 0),
+            AppEvent::WindowResizeBottomLeft.tag(HitTag::None),
         );
         builder.push_hit_test(
             LayoutRect::from_origin_and_size(
@@ -206,7 +402,7 @@ impl App {
             clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
             PrimitiveFlags::empty(),
-            (AppEvent::WindowResizeBottomRight.into(), 0),
+            AppEvent::WindowResizeBottomRight.tag(HitTag::None),
         );
     }
 }
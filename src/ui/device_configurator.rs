@@ -1,30 +1,36 @@
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crate::animation::{Animation, AnimationCurve};
+use crate::connection::MAX_PACKET_SIZE;
+use crate::profile::Profile;
 use crate::window::ext::{ColorFTrait, DisplayListBuilderExt};
-use crate::window::{Font, FrameBuilder, GlobalStateTrait, Text, WindowWrapper};
+use crate::window::{FrameBuilder, GlobalStateTrait, Text, TextAlignment, WindowWrapper};
 use crate::{ConnectionEvent, GlobalState};
 
-use super::{AppEvent, AppEventType, DocumentTrait};
+use super::text_input::{TextInput, TextInputEvent};
+use super::{AppEvent, AppEventType, DocumentTrait, HitTag};
 
 use copypasta::{ClipboardContext, ClipboardProvider};
 use hashbrown::HashSet;
-use util::connection::command::DeviceConfig;
+use log::warn;
+use rfd::FileDialog;
+use util::connection::command::{CommandTrait, DeviceConfig};
 use util::thread::MutexTrait;
-use util::time::Timer;
+use util::tokenizer::tokenize;
 use webrender::api::units::{
-    LayoutPoint, LayoutRect, LayoutSideOffsets, LayoutSize, LayoutTransform,
+    LayoutPoint, LayoutRect, LayoutSideOffsets, LayoutSize, LayoutTransform, LayoutVector3D,
 };
 use webrender::api::{
     BorderDetails, BorderRadius, BorderSide, BorderStyle, ClipMode, ColorF, CommonItemProperties,
-    DisplayListBuilder, DynamicProperties, GlyphOptions, HitTestResultItem, NormalBorder,
-    PrimitiveFlags, PropertyBinding, PropertyBindingKey, PropertyValue, ReferenceFrameKind,
-    SpaceAndClipInfo, SpatialTreeItemKey, TransformStyle,
+    DynamicProperties, HitTestResultItem, NormalBorder, PrimitiveFlags, PropertyBinding,
+    PropertyBindingKey, PropertyValue, ReferenceFrameKind, SpaceAndClipInfo, SpatialTreeItemKey,
+    TransformStyle,
 };
 use webrender::euclid::Angle;
-use webrender::{RenderApi, Transaction};
-use winit::event::VirtualKeyCode;
+use webrender::Transaction;
+use winit::event::{ModifiersState, VirtualKeyCode};
 
 struct Mode {
     name: Text,
@@ -32,199 +38,12 @@ struct Mode {
     mode: u8,
 }
 
-struct TextInput {
-    text: String,
-    focused: bool,
-    first_text: Text,
-    second_text: Text,
-    width: f32,
-    height: f32,
-    cursor_height: f32,
-    cursor_position: usize,
-    cursor_color_key: PropertyBindingKey<ColorF>,
-    cursor_color: ColorF,
-    cursor_color_state: bool,
-    cursor_timer: Timer,
-}
-
-impl TextInput {
-    fn new(
-        mut text: String,
-        font: &Font,
-        api_mutex: &Mutex<RenderApi>,
-        cursor_color: ColorF,
-        cursor_height: f32,
-    ) -> Self {
-        text.retain(|c| c != '\n' && c != '\r');
-
-        let first_text = font.create_text(text[..0].to_string(), None);
-        let second_text = font.create_text(text[0..].to_string(), None);
-
-        Self {
-            text,
-            focused: false,
-            first_text,
-            width: second_text.size.width,
-            height: second_text.size.height,
-            second_text,
-            cursor_height,
-            cursor_position: 0,
-            cursor_color_key: api_mutex.lock_poisoned().generate_property_binding_key(),
-            cursor_color,
-            cursor_color_state: true,
-            cursor_timer: Timer::new(Duration::from_millis(350)),
-        }
-    }
-
-    fn set_focus(&mut self, focus: bool) {
-        self.focused = focus;
-        self.width = self.first_text.size.width
-            + self.second_text.size.width
-            + (self.focused as u8 as f32 * 5.0);
-    }
-
-    fn update_text(&mut self, font: &Font) {
-        let (first_text, second_text) = self.text.split_at(self.cursor_position);
-
-        self.first_text = font.create_text(first_text.to_string(), None);
-        self.second_text = font.create_text(second_text.to_string(), None);
-        self.width = self.first_text.size.width
-            + self.second_text.size.width
-            + (self.focused as u8 as f32 * 5.0);
-        self.height = self
-            .first_text
-            .size
-            .height
-            .max(self.second_text.size.height);
-    }
-
-    fn add_char(&mut self, font: &Font, char: char) {
-        self.text.insert(self.cursor_position, char);
-        self.cursor_position += 1;
-
-        while !self.text.is_char_boundary(self.cursor_position) {
-            self.cursor_position += 1;
-        }
-
-        self.update_text(font);
-    }
-
-    fn add_str(&mut self, font: &Font, text: &str) {
-        self.text.insert_str(self.cursor_position, text);
-        self.cursor_position += text.len();
-        self.update_text(font);
-    }
-
-    fn delete_char(&mut self, font: &Font) {
-        if self.text.len() > self.cursor_position {
-            self.text.remove(self.cursor_position);
-        }
-
-        self.update_text(font);
-    }
-
-    fn back_char(&mut self, font: &Font) {
-        if self.cursor_position > 0 {
-            self.cursor_position -= 1;
-
-            while !self.text.is_char_boundary(self.cursor_position) {
-                self.cursor_position -= 1;
-            }
-
-            self.text.remove(self.cursor_position);
-        }
-
-        self.update_text(font);
-    }
-
-    fn change_cursor_position(&mut self, font: &Font, cursor_position: usize) {
-        self.cursor_position = cursor_position.min(self.text.len());
-
-        while !self.text.is_char_boundary(self.cursor_position) {
-            self.cursor_position += 1;
-        }
-
-        self.update_text(font);
-    }
-
-    fn cursor_left(&mut self, font: &Font) {
-        if self.cursor_position > 0 {
-            self.cursor_position -= 1;
-
-            while !self.text.is_char_boundary(self.cursor_position) {
-                self.cursor_position -= 1;
-            }
-
-            self.change_cursor_position(font, self.cursor_position);
-        }
-    }
-
-    fn cursor_right(&mut self, font: &Font) {
-        if self.cursor_position < usize::MAX {
-            self.change_cursor_position(font, self.cursor_position + 1);
-        }
-    }
-
-    fn animate(&mut self) -> Option<PropertyValue<ColorF>> {
-        if self.cursor_timer.check() {
-            self.cursor_color_state = !self.cursor_color_state;
-
-            Some(PropertyValue {
-                key: self.cursor_color_key,
-                value: if self.cursor_color_state {
-                    self.cursor_color
-                } else {
-                    ColorF::TRANSPARENT
-                },
-            })
-        } else {
-            None
-        }
-    }
-
-    fn push_text(
-        &self,
-        builder: &mut DisplayListBuilder,
-        space_and_clip: SpaceAndClipInfo,
-        position: LayoutPoint,
-        color: ColorF,
-        glyph_options: Option<GlyphOptions>,
-    ) {
-        self.first_text
-            .push_text(builder, space_and_clip, position, color, glyph_options);
-
-        if self.focused {
-            let cursor_layout_rect = LayoutRect::from_origin_and_size(
-                position + LayoutSize::new(self.first_text.size.width + 2.0, 0.0),
-                LayoutSize::new(1.0, self.cursor_height),
-            );
-            let cursor_common_item_properties =
-                &CommonItemProperties::new(cursor_layout_rect, space_and_clip);
-
-            builder.push_rect_with_animation(
-                cursor_common_item_properties,
-                cursor_layout_rect,
-                PropertyBinding::Binding(self.cursor_color_key, self.cursor_color),
-            );
-        }
-
-        self.second_text.push_text(
-            builder,
-            space_and_clip,
-            position
-                + LayoutSize::new(
-                    self.first_text.size.width + (self.focused as u8 as f32 * 5.0),
-                    0.0,
-                ),
-            color,
-            glyph_options,
-        );
-    }
-}
-
 struct Parameter {
     name: Text,
     value: TextInput,
+    is_valid: bool,
+    over_color_animation: Animation<ColorF>,
+    over_color_key: PropertyBindingKey<ColorF>,
 }
 
 pub struct DeviceConfigurator {
@@ -232,15 +51,57 @@ pub struct DeviceConfigurator {
     parameter_vec: Vec<Parameter>,
     apply_configcurrent_focused_parameter_index_option: Option<usize>,
     current_mode: usize,
+    applied_config_option: Option<DeviceConfig>,
+    // false while a value of any mode is rejected by the tokenizer
+    config_valid: bool,
+    dirty: bool,
+    leave_armed: bool,
+    apply_to_all: bool,
+    apply_to_all_armed: bool,
+    // shift held, the reset button then clears every mode
+    shift_pressed: bool,
+    // last mouse position in the document, to select text in the parameters
+    mouse_position: LayoutPoint,
+    // values of every parameter of the copied mode
+    copied_mode_option: Option<Vec<String>>,
     device_info_text: Text,
     apply_config_text: Text,
-    clipboard_context: ClipboardContext,
+    confirm_apply_config_text: Text,
+    apply_to_all_text: Text,
+    reset_config_text: Text,
+    reset_all_config_text: Text,
+    copy_mode_text: Text,
+    paste_mode_text: Text,
+    export_profile_text: Text,
+    import_profile_text: Text,
+    reset_parameter_text: Text,
+    capture_parameter_text: Text,
+    capturing_parameter_text: Text,
+    capturing_parameter_index_option: Option<usize>,
+    message_option: Option<(Text, ColorF, Instant)>,
+    clipboard_context_option: Option<ClipboardContext>,
     mode_selector_previous_button_color_key: PropertyBindingKey<ColorF>,
     mode_selector_next_button_color_key: PropertyBindingKey<ColorF>,
     apply_config_button_color_key: PropertyBindingKey<ColorF>,
+    reset_config_button_color_key: PropertyBindingKey<ColorF>,
+    copy_mode_button_color_key: PropertyBindingKey<ColorF>,
+    paste_mode_button_color_key: PropertyBindingKey<ColorF>,
+    export_profile_button_color_key: PropertyBindingKey<ColorF>,
+    import_profile_button_color_key: PropertyBindingKey<ColorF>,
+    apply_to_all_button_color_key: PropertyBindingKey<ColorF>,
     mode_selector_previous_button_color_animation: Animation<ColorF>,
     mode_selector_next_button_color_animation: Animation<ColorF>,
     apply_config_button_color_animation: Animation<ColorF>,
+    reset_config_button_color_animation: Animation<ColorF>,
+    copy_mode_button_color_animation: Animation<ColorF>,
+    paste_mode_button_color_animation: Animation<ColorF>,
+    export_profile_button_color_animation: Animation<ColorF>,
+    import_profile_button_color_animation: Animation<ColorF>,
+    apply_to_all_button_color_animation: Animation<ColorF>,
+    mode_selector_previous_arrow_transform_key: PropertyBindingKey<LayoutTransform>,
+    mode_selector_next_arrow_transform_key: PropertyBindingKey<LayoutTransform>,
+    mode_selector_previous_arrow_transform_animation: Animation<LayoutTransform>,
+    mode_selector_next_arrow_transform_animation: Animation<LayoutTransform>,
 }
 
 impl DeviceConfigurator {
@@ -261,6 +122,14 @@ impl DeviceConfigurator {
             mode_selector_previous_button_color_key,
             mode_selector_next_button_color_key,
             apply_config_button_color_key,
+            reset_config_button_color_key,
+            copy_mode_button_color_key,
+            paste_mode_button_color_key,
+            export_profile_button_color_key,
+            import_profile_button_color_key,
+            apply_to_all_button_color_key,
+            mode_selector_previous_arrow_transform_key,
+            mode_selector_next_arrow_transform_key,
         ) = {
             let api = wrapper.api_mutex.lock_poisoned();
 
@@ -268,8 +137,17 @@ impl DeviceConfigurator {
                 api.generate_property_binding_key(),
                 api.generate_property_binding_key(),
                 api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
+                api.generate_property_binding_key(),
             )
         };
+        let arrow_transform_animation = Animation::new_transform(arrow_transform(0.0));
         let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
 
         Self {
@@ -277,6 +155,15 @@ impl DeviceConfigurator {
             parameter_vec: vec![],
             apply_configcurrent_focused_parameter_index_option: None,
             current_mode: 0,
+            applied_config_option: None,
+            config_valid: true,
+            dirty: false,
+            leave_armed: false,
+            apply_to_all: false,
+            apply_to_all_armed: false,
+            shift_pressed: false,
+            mouse_position: LayoutPoint::zero(),
+            copied_mode_option: None,
             device_info_text: font_hashmap["OpenSans_13px"].create_text(
                 format!(
                     "Selected device : {} | {} n°",
@@ -289,13 +176,60 @@ impl DeviceConfigurator {
             ),
             apply_config_text: font_hashmap["OpenSans_13px"]
                 .create_text("Apply config".to_string(), None),
-            clipboard_context: ClipboardContext::new().unwrap(),
+            confirm_apply_config_text: font_hashmap["OpenSans_13px"]
+                .create_text("Confirm apply to all".to_string(), None),
+            apply_to_all_text: font_hashmap["OpenSans_13px"]
+                .create_text("Apply to all devices".to_string(), None),
+            reset_config_text: font_hashmap["OpenSans_13px"]
+                .create_text("Reset mode".to_string(), None),
+            reset_all_config_text: font_hashmap["OpenSans_13px"]
+                .create_text("Reset all modes".to_string(), None),
+            copy_mode_text: font_hashmap["OpenSans_13px"]
+                .create_text("Copy mode".to_string(), None),
+            paste_mode_text: font_hashmap["OpenSans_13px"]
+                .create_text("Paste mode".to_string(), None),
+            export_profile_text: font_hashmap["OpenSans_13px"]
+                .create_text("Export".to_string(), None),
+            import_profile_text: font_hashmap["OpenSans_13px"]
+                .create_text("Import".to_string(), None),
+            reset_parameter_text: font_hashmap["OpenSans_13px"]
+                .create_text("Reset".to_string(), None),
+            capture_parameter_text: font_hashmap["OpenSans_13px"]
+                .create_text("Capture".to_string(), None),
+            capturing_parameter_text: font_hashmap["OpenSans_13px"]
+                .create_text("Press a key".to_string(), None),
+            capturing_parameter_index_option: None,
+            message_option: None,
+            // copy and paste are disabled when no clipboard backend is available
+            clipboard_context_option: match ClipboardContext::new() {
+                Ok(clipboard_context) => Some(clipboard_context),
+                Err(error) => {
+                    warn!("Failed to initialize the clipboard : {}", error);
+                    None
+                }
+            },
             mode_selector_previous_button_color_key,
             mode_selector_next_button_color_key,
             apply_config_button_color_key,
+            reset_config_button_color_key,
+            copy_mode_button_color_key,
+            paste_mode_button_color_key,
+            export_profile_button_color_key,
+            import_profile_button_color_key,
+            apply_to_all_button_color_key,
             mode_selector_previous_button_color_animation: button_color_animation.clone(),
             mode_selector_next_button_color_animation: button_color_animation.clone(),
-            apply_config_button_color_animation: button_color_animation,
+            apply_config_button_color_animation: button_color_animation.clone(),
+            reset_config_button_color_animation: button_color_animation.clone(),
+            copy_mode_button_color_animation: button_color_animation.clone(),
+            paste_mode_button_color_animation: button_color_animation.clone(),
+            export_profile_button_color_animation: button_color_animation.clone(),
+            import_profile_button_color_animation: button_color_animation.clone(),
+            apply_to_all_button_color_animation: button_color_animation,
+            mode_selector_previous_arrow_transform_key,
+            mode_selector_next_arrow_transform_key,
+            mode_selector_previous_arrow_transform_animation: arrow_transform_animation.clone(),
+            mode_selector_next_arrow_transform_animation: arrow_transform_animation,
         }
     }
 
@@ -324,224 +258,775 @@ impl DeviceConfigurator {
 
             wrapper.global_state.request_redraw();
         }
+
+        self.validate_parameters(&wrapper.global_state.selected_device_config_option_mutex);
+    }
+
+    fn validate_parameters(
+        &mut self,
+        selected_device_config_option_mutex: &Mutex<Option<DeviceConfig>>,
+    ) {
+        if self.mode_vec.is_empty() {
+            return;
+        }
+
+        if let Some(selected_device_config) =
+            selected_device_config_option_mutex.lock_poisoned().as_ref()
+        {
+            let is_shift_mode = self.mode_vec[self.current_mode].is_shift_mode;
+            let mode = self.mode_vec[self.current_mode].mode;
+
+            for (index, parameter) in self.parameter_vec.iter_mut().enumerate() {
+                parameter.is_valid = validate_config_string(
+                    &selected_device_config.config[index][is_shift_mode as usize][mode as usize],
+                );
+            }
+
+            // values of the other modes are checked too, they are sent with the config
+            self.config_valid = selected_device_config
+                .config
+                .iter()
+                .flatten()
+                .flatten()
+                .all(|value| validate_config_string(value));
+        }
     }
 
     fn update_selected_config(
-        &self,
+        &mut self,
         selected_device_config_option_mutex: &Mutex<Option<DeviceConfig>>,
     ) {
-        if let (Some(current_focused_parameter), Some(selected_device_config)) = (
-            self.apply_configcurrent_focused_parameter_index_option,
-            selected_device_config_option_mutex.lock_poisoned().as_mut(),
-        ) {
+        if let Some(current_focused_parameter) =
+            self.apply_configcurrent_focused_parameter_index_option
+        {
+            self.update_selected_config_parameter(
+                current_focused_parameter,
+                selected_device_config_option_mutex,
+            );
+        }
+    }
+
+    fn update_selected_config_parameter(
+        &mut self,
+        index: usize,
+        selected_device_config_option_mutex: &Mutex<Option<DeviceConfig>>,
+    ) {
+        if let Some(selected_device_config) =
+            selected_device_config_option_mutex.lock_poisoned().as_mut()
+        {
             let is_shift_mode = self.mode_vec[self.current_mode].is_shift_mode;
             let mode = self.mode_vec[self.current_mode].mode;
 
-            selected_device_config.config[current_focused_parameter][is_shift_mode as usize]
-                [mode as usize] = self.parameter_vec[current_focused_parameter]
-                .value
-                .text
-                .clone();
+            selected_device_config.config[index][is_shift_mode as usize][mode as usize] =
+                self.parameter_vec[index].value.text.clone();
+
+            self.dirty = true;
+            self.leave_armed = false;
         }
     }
-}
 
-impl DocumentTrait for DeviceConfigurator {
-    fn get_title(&self) -> &'static str {
-        "Device Configuration"
+    fn reset_parameter(&mut self, index: usize, wrapper: &mut WindowWrapper<GlobalState>) {
+        let is_shift_mode = self.mode_vec[self.current_mode].is_shift_mode;
+        let mode = self.mode_vec[self.current_mode].mode;
+        // restore the last loaded or applied value, or an empty one
+        let value = match self.applied_config_option.as_ref() {
+            Some(applied_config) => {
+                applied_config.config[index][is_shift_mode as usize][mode as usize].clone()
+            }
+            None => String::new(),
+        };
+
+        self.set_parameter_value(index, value, wrapper);
     }
 
-    fn calculate_event(
+    fn set_parameter_value(
         &mut self,
-        hit_items: &Vec<HitTestResultItem>,
+        index: usize,
+        value: String,
         wrapper: &mut WindowWrapper<GlobalState>,
-        target_event_type: AppEventType,
     ) {
-        // parameters text input event logic
-        if let Some(current_focused_parameter_index) =
-            self.apply_configcurrent_focused_parameter_index_option
-        {
-            let current_focused_parameter =
-                &mut self.parameter_vec[current_focused_parameter_index].value;
+        let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
 
-            match target_event_type {
-                AppEventType::MousePressed | AppEventType::Focus(false) => {
-                    for parameter in self.parameter_vec.iter_mut() {
-                        parameter.value.set_focus(false);
-                    }
+        self.parameter_vec[index].value = TextInput::new(
+            value,
+            &font_hashmap["OpenSans_13px"],
+            &wrapper.api_mutex,
+            ColorF::WHITE,
+            17.0,
+        );
 
-                    self.apply_configcurrent_focused_parameter_index_option = None;
+        // keep the focus if the parameter was the focused one
+        if self.apply_configcurrent_focused_parameter_index_option == Some(index) {
+            self.parameter_vec[index].value.set_focus(true);
+        }
 
-                    wrapper.global_state.request_redraw();
-                }
-                AppEventType::KeyPressed { keycode, modifiers } => {
-                    let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
+        self.update_selected_config_parameter(
+            index,
+            &wrapper.global_state.selected_device_config_option_mutex,
+        );
+        self.validate_parameters(&wrapper.global_state.selected_device_config_option_mutex);
 
-                    match keycode {
-                        VirtualKeyCode::Left => {
-                            current_focused_parameter.cursor_left(&font_hashmap["OpenSans_13px"]);
-                            wrapper.global_state.request_redraw();
-                        }
-                        VirtualKeyCode::Right => {
-                            current_focused_parameter.cursor_right(&font_hashmap["OpenSans_13px"]);
-                            wrapper.global_state.request_redraw();
-                        }
-                        VirtualKeyCode::Delete => {
-                            current_focused_parameter.delete_char(&font_hashmap["OpenSans_13px"]);
+        wrapper.global_state.request_redraw();
+    }
 
-                            self.update_selected_config(
-                                &wrapper.global_state.selected_device_config_option_mutex,
-                            );
+    fn show_message(
+        &mut self,
+        message: String,
+        color: ColorF,
+        wrapper: &WindowWrapper<GlobalState>,
+    ) {
+        self.message_option = Some((
+            wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"]
+                .create_text(message, None),
+            color,
+            Instant::now(),
+        ));
 
-                            wrapper.global_state.request_redraw();
-                        }
-                        VirtualKeyCode::Back => {
-                            current_focused_parameter.back_char(&font_hashmap["OpenSans_13px"]);
+        wrapper.global_state.request_redraw();
+    }
 
-                            self.update_selected_config(
-                                &wrapper.global_state.selected_device_config_option_mutex,
-                            );
+    fn apply_config(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        let driver_hashmap = wrapper.global_state.driver_hashmap_mutex.lock_poisoned();
 
-                            wrapper.global_state.request_redraw();
-                        }
-                        VirtualKeyCode::C | VirtualKeyCode::X => {
-                            if modifiers.ctrl() {
-                                self.clipboard_context
-                                    .set_contents(current_focused_parameter.text.clone())
-                                    .ok();
-                            }
-                        }
-                        VirtualKeyCode::V => {
-                            if modifiers.ctrl() {
-                                if let Ok(mut text) = self.clipboard_context.get_contents() {
-                                    text.retain(|c| c != '\n' && c != '\r');
-                                    current_focused_parameter
-                                        .add_str(&font_hashmap["OpenSans_13px"], text.as_str());
-
-                                    self.update_selected_config(
-                                        &wrapper.global_state.selected_device_config_option_mutex,
-                                    );
+        if let (Some(selected_device_id), Some(selected_device_config)) = (
+            wrapper
+                .global_state
+                .selected_device_id_option_mutex
+                .lock_poisoned()
+                .as_ref(),
+            wrapper
+                .global_state
+                .selected_device_config_option_mutex
+                .lock_poisoned()
+                .as_ref(),
+        ) {
+            let config_size = selected_device_config.clone().to_bytes().len();
+
+            // the driver would silently drop the packet
+            if config_size > MAX_PACKET_SIZE {
+                self.show_message(
+                    format!(
+                        "Config too large to be sent ({:.1} MB, max {} MB)",
+                        config_size as f32 / 1_000_000.0,
+                        MAX_PACKET_SIZE / 1_000_000
+                    ),
+                    ColorF::new_u(231, 76, 60, 255),
+                    wrapper,
+                );
 
-                                    wrapper.global_state.request_redraw();
-                                }
-                            }
-                        }
-                        _ => {}
+                return;
+            }
+
+            self.applied_config_option = Some(selected_device_config.clone());
+
+            if self.apply_to_all {
+                // send the config to every device of the same driver
+                if let Some(driver) = driver_hashmap.get(&selected_device_id.socket_addr) {
+                    for serial_number in driver.device_list.serial_number_vec.iter() {
+                        let mut device_config = selected_device_config.clone();
+
+                        device_config.serial_number = serial_number.clone();
+                        wrapper.global_state.push_connection_event(
+                            ConnectionEvent::ApplyDeviceConfig(
+                                selected_device_id.socket_addr,
+                                device_config,
+                            ),
+                        );
                     }
                 }
-                AppEventType::Char(char) => {
-                    if char != '\n'
-                        && char != '\r'
-                        && char != '\u{3}'
-                        && char != '\u{8}'
-                        && char != '\u{16}'
-                        && char != '\u{18}'
-                        && char != '\u{1b}'
-                        && char != '\u{7f}'
-                    {
-                        let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
+            } else {
+                wrapper
+                    .global_state
+                    .push_connection_event(ConnectionEvent::ApplyDeviceConfig(
+                        selected_device_id.socket_addr,
+                        selected_device_config.clone(),
+                    ));
+            }
 
-                        current_focused_parameter.add_char(&font_hashmap["OpenSans_13px"], char);
+            self.dirty = false;
+            self.show_message(
+                "Config applied".to_string(),
+                ColorF::new_u(46, 204, 113, 255),
+                wrapper,
+            );
+        }
+    }
 
-                        self.update_selected_config(
-                            &wrapper.global_state.selected_device_config_option_mutex,
-                        );
+    // clear the current mode, or every mode, and send the cleared config
+    fn reset_config(&mut self, all_modes: bool, wrapper: &mut WindowWrapper<GlobalState>) {
+        if let Some(selected_device_config) = wrapper
+            .global_state
+            .selected_device_config_option_mutex
+            .lock_poisoned()
+            .as_mut()
+        {
+            let is_shift_mode = self.mode_vec[self.current_mode].is_shift_mode;
+            let mode = self.mode_vec[self.current_mode].mode;
 
-                        wrapper.global_state.request_redraw();
+            for config in selected_device_config.config.iter_mut() {
+                if all_modes {
+                    for value in config.iter_mut().flatten() {
+                        value.clear();
                     }
+                } else {
+                    config[is_shift_mode as usize][mode as usize].clear();
                 }
-                _ => {}
             }
+
+            // stays unsaved if the apply is refused or waits for a confirmation
+            self.dirty = true;
+            self.leave_armed = false;
         }
 
-        if !hit_items.is_empty() {
-            if let Some(event) = AppEvent::from(hit_items[0].tag.0) {
-                match target_event_type {
-                    AppEventType::MouseReleased => match event {
-                        AppEvent::ModeSelectorPrevious => {
-                            if self.current_mode == 0 {
-                                self.current_mode = self.mode_vec.len() - 1;
-                            } else {
-                                self.current_mode -= 1;
-                            }
+        self.update_parameter(wrapper);
+        self.request_apply_config(wrapper);
+    }
 
-                            self.update_parameter(wrapper);
-                        }
-                        AppEvent::ModeSelectorNext => {
-                            if self.current_mode == self.mode_vec.len() - 1 {
-                                self.current_mode = 0;
-                            } else {
-                                self.current_mode += 1;
-                            }
+    fn copy_mode(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if let Some(selected_device_config) = wrapper
+            .global_state
+            .selected_device_config_option_mutex
+            .lock_poisoned()
+            .as_ref()
+        {
+            let is_shift_mode = self.mode_vec[self.current_mode].is_shift_mode;
+            let mode = self.mode_vec[self.current_mode].mode;
 
-                            self.update_parameter(wrapper);
-                        }
-                        AppEvent::ApplyConfig => {
-                            if let (Some(selected_device_id), Some(selected_device_config)) = (
-                                wrapper
-                                    .global_state
-                                    .selected_device_id_option_mutex
-                                    .lock_poisoned()
-                                    .as_ref(),
-                                wrapper
-                                    .global_state
-                                    .selected_device_config_option_mutex
-                                    .lock_poisoned()
-                                    .as_ref(),
-                            ) {
-                                wrapper.global_state.push_connection_event(
-                                    ConnectionEvent::ApplyDeviceConfig(
-                                        selected_device_id.socket_addr,
-                                        selected_device_config.clone(),
-                                    ),
-                                );
-                            }
-                        }
-                        AppEvent::Parameter => {
-                            self.parameter_vec[hit_items[0].tag.1 as usize]
-                                .value
-                                .set_focus(true);
-                            self.apply_configcurrent_focused_parameter_index_option =
-                                Some(hit_items[0].tag.1 as usize);
+            self.copied_mode_option = Some(
+                selected_device_config
+                    .config
+                    .iter()
+                    .map(|config| config[is_shift_mode as usize][mode as usize].clone())
+                    .collect(),
+            );
+        }
 
-                            wrapper.global_state.request_redraw();
-                        }
-                        _ => {}
-                    },
-                    _ => {}
-                }
+        let message = format!(
+            "{} copied",
+            self.mode_vec[self.current_mode]
+                .name
+                .char_vec
+                .iter()
+                .collect::<String>()
+        );
+
+        self.show_message(message, ColorF::new_u(46, 204, 113, 255), wrapper);
+    }
+
+    // a shift mode and a normal mode share the same layout, so any mode can be pasted on any other
+    fn paste_mode(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if let (Some(copied_mode), Some(selected_device_config)) = (
+            self.copied_mode_option.as_ref(),
+            wrapper
+                .global_state
+                .selected_device_config_option_mutex
+                .lock_poisoned()
+                .as_mut(),
+        ) {
+            let is_shift_mode = self.mode_vec[self.current_mode].is_shift_mode;
+            let mode = self.mode_vec[self.current_mode].mode;
+
+            for (config, value) in selected_device_config.config.iter_mut().zip(copied_mode) {
+                config[is_shift_mode as usize][mode as usize] = value.clone();
             }
+
+            self.dirty = true;
+            self.leave_armed = false;
         }
+
+        self.update_parameter(wrapper);
     }
 
-    fn update_over_state(&mut self, new_over_state: &HashSet<(AppEvent, u16)>) {
-        if new_over_state.contains(&(AppEvent::ModeSelectorPrevious, 0)) {
-            self.mode_selector_previous_button_color_animation.to(
-                ColorF::new_u(33, 33, 33, 100),
-                Duration::from_millis(100),
-                AnimationCurve::EASE_OUT,
-            );
-        } else {
-            self.mode_selector_previous_button_color_animation.to(
-                ColorF::new_u(33, 33, 33, 0),
-                Duration::from_millis(100),
+    // a dropped profile is only loaded, an imported one is applied right away
+    fn import_profile(
+        &mut self,
+        path: &Path,
+        apply: bool,
+        wrapper: &mut WindowWrapper<GlobalState>,
+    ) {
+        let result = Profile::import(path).and_then(|profile| {
+            let driver_hashmap = wrapper.global_state.driver_hashmap_mutex.lock_poisoned();
+            let selected_device_id_option = wrapper
+                .global_state
+                .selected_device_id_option_mutex
+                .lock_poisoned();
+            let mut selected_device_config_option = wrapper
+                .global_state
+                .selected_device_config_option_mutex
+                .lock_poisoned();
+
+            match (
+                selected_device_id_option
+                    .as_ref()
+                    .and_then(|device_id| driver_hashmap.get(&device_id.socket_addr)),
+                selected_device_config_option.as_mut(),
+            ) {
+                (Some(driver), Some(selected_device_config)) => {
+                    profile.validate(&driver.driver_configuration_descriptor)?;
+
+                    // the serial number of the selected device is kept
+                    selected_device_config.config = profile.config;
+
+                    Ok(())
+                }
+                _ => Err("No device selected".to_string()),
+            }
+        });
+
+        match result {
+            Ok(()) => {
+                self.dirty = true;
+                self.leave_armed = false;
+                self.update_parameter(wrapper);
+
+                if apply {
+                    self.request_apply_config(wrapper);
+                } else {
+                    self.show_message(
+                        "Profile imported, apply it to save it on the device".to_string(),
+                        ColorF::new_u(46, 204, 113, 255),
+                        wrapper,
+                    );
+                }
+            }
+            Err(error) => self.show_message(error, ColorF::new_u(231, 76, 60, 255), wrapper),
+        }
+    }
+
+    fn export_profile(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        let profile_option = {
+            let driver_hashmap = wrapper.global_state.driver_hashmap_mutex.lock_poisoned();
+            let selected_device_id_option = wrapper
+                .global_state
+                .selected_device_id_option_mutex
+                .lock_poisoned();
+            let selected_device_config_option = wrapper
+                .global_state
+                .selected_device_config_option_mutex
+                .lock_poisoned();
+
+            match (
+                selected_device_id_option
+                    .as_ref()
+                    .and_then(|device_id| driver_hashmap.get(&device_id.socket_addr)),
+                selected_device_config_option.as_ref(),
+            ) {
+                (Some(driver), Some(selected_device_config)) => Some(Profile {
+                    device_name: driver.driver_configuration_descriptor.device_name.clone(),
+                    config: selected_device_config.config.clone(),
+                }),
+                _ => None,
+            }
+        };
+
+        if let Some(profile) = profile_option {
+            // the dialog blocks until a file is chosen or it is cancelled
+            if let Some(path) = FileDialog::new()
+                .add_filter("Profile", &["json"])
+                .set_file_name(&format!("{}.json", profile.device_name))
+                .save_file()
+            {
+                match profile.export(&path) {
+                    Ok(()) => self.show_message(
+                        "Profile exported".to_string(),
+                        ColorF::new_u(46, 204, 113, 255),
+                        wrapper,
+                    ),
+                    Err(error) => {
+                        self.show_message(error, ColorF::new_u(231, 76, 60, 255), wrapper)
+                    }
+                }
+            }
+        }
+    }
+
+    fn pick_profile_to_import(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if let Some(path) = FileDialog::new()
+            .add_filter("Profile", &["json"])
+            .pick_file()
+        {
+            self.import_profile(&path, true, wrapper);
+        }
+    }
+
+    fn request_apply_config(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        // the focused value is only validated when losing the focus, so check again
+        self.validate_parameters(&wrapper.global_state.selected_device_config_option_mutex);
+
+        // malformed configs never reach the driver
+        if !self.config_valid {
+            self.show_message(
+                "Some parameters are not valid macros".to_string(),
+                ColorF::new_u(231, 76, 60, 255),
+                wrapper,
+            );
+
+            return;
+        }
+
+        if self.apply_to_all && !self.apply_to_all_armed {
+            // ask for a confirmation before applying to every device
+            self.apply_to_all_armed = true;
+        } else {
+            self.apply_to_all_armed = false;
+            self.apply_config(wrapper);
+        }
+
+        wrapper.global_state.request_redraw();
+    }
+}
+
+impl DocumentTrait for DeviceConfigurator {
+    fn get_title(&self) -> &'static str {
+        "Device Configuration"
+    }
+
+    fn can_leave(&mut self, wrapper: &mut WindowWrapper<GlobalState>) -> bool {
+        // a second try discards the unsaved changes
+        if !self.dirty || self.leave_armed {
+            return true;
+        }
+
+        self.leave_armed = true;
+        self.show_message(
+            "Unsaved changes, leave or close again to discard them".to_string(),
+            ColorF::new_u(230, 126, 34, 255),
+            wrapper,
+        );
+
+        false
+    }
+
+    fn file_dropped(&mut self, path: PathBuf, wrapper: &mut WindowWrapper<GlobalState>) {
+        self.import_profile(&path, false, wrapper);
+    }
+
+    fn is_capturing_keys(&self) -> bool {
+        self.apply_configcurrent_focused_parameter_index_option
+            .is_some()
+            || self.capturing_parameter_index_option.is_some()
+    }
+
+    fn calculate_event(
+        &mut self,
+        hit_items: &Vec<HitTestResultItem>,
+        wrapper: &mut WindowWrapper<GlobalState>,
+        target_event_type: AppEventType,
+    ) {
+        match target_event_type {
+            AppEventType::KeyPressed {
+                keycode: VirtualKeyCode::LShift | VirtualKeyCode::RShift,
+                ..
+            } => {
+                self.shift_pressed = true;
+
+                wrapper.global_state.request_redraw();
+            }
+            AppEventType::KeyReleased {
+                keycode: VirtualKeyCode::LShift | VirtualKeyCode::RShift,
+                ..
+            }
+            | AppEventType::Focus(false) => {
+                self.shift_pressed = false;
+
+                wrapper.global_state.request_redraw();
+            }
+            _ => {}
+        }
+
+        // key capture, the next key combination replaces the parameter value
+        if let Some(capturing_parameter_index) = self.capturing_parameter_index_option {
+            match target_event_type {
+                AppEventType::KeyPressed { keycode, modifiers } => {
+                    if keycode == VirtualKeyCode::Escape {
+                        self.capturing_parameter_index_option = None;
+
+                        wrapper.global_state.request_redraw();
+                    } else if let Some(tokens) = key_combination_to_tokens(keycode, modifiers) {
+                        self.capturing_parameter_index_option = None;
+                        self.set_parameter_value(capturing_parameter_index, tokens, wrapper);
+                    }
+
+                    return;
+                }
+                AppEventType::KeyReleased { .. } | AppEventType::Char(_) => return,
+                AppEventType::MousePressed | AppEventType::Focus(false) => {
+                    self.capturing_parameter_index_option = None;
+
+                    wrapper.global_state.request_redraw();
+                }
+                _ => {}
+            }
+        }
+
+        // apply config shortcut, whatever the focused parameter
+        if let AppEventType::KeyPressed {
+            keycode: VirtualKeyCode::S,
+            modifiers,
+        } = target_event_type
+        {
+            if modifiers.ctrl() {
+                self.request_apply_config(wrapper);
+            }
+        }
+
+        // parameters text input event logic
+        if let Some(current_focused_parameter_index) =
+            self.apply_configcurrent_focused_parameter_index_option
+        {
+            let current_focused_parameter =
+                &mut self.parameter_vec[current_focused_parameter_index].value;
+            let pressed_focused_parameter = hit_items
+                .first()
+                .and_then(|hit_item| AppEvent::from_tag(hit_item.tag))
+                == Some((
+                    AppEvent::Parameter,
+                    HitTag::Index(current_focused_parameter_index as u16),
+                ));
+
+            match target_event_type {
+                // the press moves the cursor of the focused parameter instead
+                AppEventType::MousePressed if pressed_focused_parameter => {}
+                AppEventType::MouseReleased => current_focused_parameter.mouse_released(),
+                AppEventType::MousePressed | AppEventType::Focus(false) => {
+                    for parameter in self.parameter_vec.iter_mut() {
+                        parameter.value.set_focus(false);
+                    }
+
+                    self.apply_configcurrent_focused_parameter_index_option = None;
+                    self.validate_parameters(
+                        &wrapper.global_state.selected_device_config_option_mutex,
+                    );
+
+                    wrapper.global_state.request_redraw();
+                }
+                AppEventType::KeyPressed {
+                    keycode: VirtualKeyCode::Tab,
+                    modifiers,
+                } => {
+                    // move the focus to the next parameter, or the previous one with shift
+                    let parameter_count = self.parameter_vec.len();
+                    let next_focused_parameter_index = if modifiers.shift() {
+                        (current_focused_parameter_index + parameter_count - 1) % parameter_count
+                    } else {
+                        (current_focused_parameter_index + 1) % parameter_count
+                    };
+
+                    self.parameter_vec[current_focused_parameter_index]
+                        .value
+                        .set_focus(false);
+                    self.parameter_vec[next_focused_parameter_index]
+                        .value
+                        .set_focus(true);
+                    self.apply_configcurrent_focused_parameter_index_option =
+                        Some(next_focused_parameter_index);
+
+                    self.validate_parameters(
+                        &wrapper.global_state.selected_device_config_option_mutex,
+                    );
+
+                    wrapper.global_state.request_redraw();
+                }
+                AppEventType::KeyPressed { keycode, modifiers } => {
+                    let text_input_event = current_focused_parameter.key_pressed(
+                        &wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"],
+                        keycode,
+                        modifiers,
+                        self.clipboard_context_option.as_mut(),
+                    );
+
+                    if text_input_event == TextInputEvent::TextChanged {
+                        self.update_selected_config(
+                            &wrapper.global_state.selected_device_config_option_mutex,
+                        );
+                    }
+                    if text_input_event != TextInputEvent::Ignored {
+                        wrapper.global_state.request_redraw();
+                    }
+                }
+                AppEventType::Char(char) => {
+                    let text_input_event = current_focused_parameter.char_received(
+                        &wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"],
+                        char,
+                    );
+
+                    if text_input_event == TextInputEvent::TextChanged {
+                        self.update_selected_config(
+                            &wrapper.global_state.selected_device_config_option_mutex,
+                        );
+
+                        wrapper.global_state.request_redraw();
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !hit_items.is_empty() {
+            if let Some((event, hit_tag)) = AppEvent::from_tag(hit_items[0].tag) {
+                match target_event_type {
+                    AppEventType::MousePressed => {
+                        if let (AppEvent::Parameter, HitTag::Index(index)) = (event, hit_tag) {
+                            let parameter = &mut self.parameter_vec[index as usize].value;
+
+                            // measured as it was drawn, before focusing adds the cursor gap
+                            parameter.mouse_pressed(
+                                &wrapper.global_state.font_hashmap_mutex.lock_poisoned()
+                                    ["OpenSans_13px"],
+                                self.mouse_position,
+                                self.shift_pressed,
+                            );
+                            parameter.set_focus(true);
+                            self.apply_configcurrent_focused_parameter_index_option =
+                                Some(index as usize);
+
+                            wrapper.global_state.request_redraw();
+                        }
+                    }
+                    AppEventType::MouseReleased => match event {
+                        AppEvent::ModeSelectorPrevious => {
+                            if self.current_mode == 0 {
+                                self.current_mode = self.mode_vec.len() - 1;
+                            } else {
+                                self.current_mode -= 1;
+                            }
+
+                            wrapper.global_state.set_last_mode(self.current_mode);
+                            self.update_parameter(wrapper);
+                        }
+                        AppEvent::ModeSelectorNext => {
+                            if self.current_mode == self.mode_vec.len() - 1 {
+                                self.current_mode = 0;
+                            } else {
+                                self.current_mode += 1;
+                            }
+
+                            wrapper.global_state.set_last_mode(self.current_mode);
+                            self.update_parameter(wrapper);
+                        }
+                        AppEvent::ApplyConfig => self.request_apply_config(wrapper),
+                        AppEvent::ResetConfig => self.reset_config(self.shift_pressed, wrapper),
+                        AppEvent::CopyMode => self.copy_mode(wrapper),
+                        AppEvent::PasteMode => self.paste_mode(wrapper),
+                        AppEvent::ExportProfile => self.export_profile(wrapper),
+                        AppEvent::ImportProfile => self.pick_profile_to_import(wrapper),
+                        AppEvent::CopySerialNumber => {
+                            let serial_number_option = wrapper
+                                .global_state
+                                .selected_device_id_option_mutex
+                                .lock_poisoned()
+                                .as_ref()
+                                .map(|selected_device_id| selected_device_id.serial_number.clone());
+
+                            if let (Some(serial_number), Some(clipboard_context)) =
+                                (serial_number_option, self.clipboard_context_option.as_mut())
+                            {
+                                if clipboard_context.set_contents(serial_number).is_ok() {
+                                    self.show_message(
+                                        "Serial number copied".to_string(),
+                                        ColorF::new_u(46, 204, 113, 255),
+                                        wrapper,
+                                    );
+                                }
+                            }
+                        }
+                        AppEvent::ApplyToAllCheckbox => {
+                            self.apply_to_all = !self.apply_to_all;
+                            self.apply_to_all_armed = false;
+
+                            wrapper.global_state.request_redraw();
+                        }
+                        AppEvent::ResetParameter => {
+                            if let HitTag::Index(index) = hit_tag {
+                                self.reset_parameter(index as usize, wrapper);
+                            }
+                        }
+                        AppEvent::CaptureParameter => {
+                            if let HitTag::Index(index) = hit_tag {
+                                self.capturing_parameter_index_option = Some(index as usize);
+
+                                wrapper.global_state.request_redraw();
+                            }
+                        }
+                        _ => {}
+                    },
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn mouse_moved(
+        &mut self,
+        mouse_position: LayoutPoint,
+        wrapper: &mut WindowWrapper<GlobalState>,
+    ) {
+        self.mouse_position = mouse_position;
+
+        if let Some(current_focused_parameter_index) =
+            self.apply_configcurrent_focused_parameter_index_option
+        {
+            let text_input_event = self.parameter_vec[current_focused_parameter_index]
+                .value
+                .mouse_moved(
+                    &wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"],
+                    mouse_position,
+                );
+
+            if text_input_event != TextInputEvent::Ignored {
+                wrapper.global_state.request_redraw();
+            }
+        }
+    }
+
+    fn update_over_state(&mut self, new_over_state: &HashSet<(AppEvent, HitTag)>) {
+        if new_over_state.contains(&(AppEvent::ModeSelectorPrevious, HitTag::None)) {
+            self.mode_selector_previous_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+            self.mode_selector_previous_arrow_transform_animation.to(
+                arrow_transform(-3.0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.mode_selector_previous_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+            self.mode_selector_previous_arrow_transform_animation.to(
+                arrow_transform(0.0),
+                Duration::from_millis(100),
                 AnimationCurve::EASE_IN,
             );
         }
-        if new_over_state.contains(&(AppEvent::ModeSelectorNext, 0)) {
+        if new_over_state.contains(&(AppEvent::ModeSelectorNext, HitTag::None)) {
             self.mode_selector_next_button_color_animation.to(
                 ColorF::new_u(33, 33, 33, 100),
                 Duration::from_millis(100),
                 AnimationCurve::EASE_OUT,
             );
+            self.mode_selector_next_arrow_transform_animation.to(
+                arrow_transform(3.0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
         } else {
             self.mode_selector_next_button_color_animation.to(
                 ColorF::new_u(33, 33, 33, 0),
                 Duration::from_millis(100),
                 AnimationCurve::EASE_IN,
             );
+            self.mode_selector_next_arrow_transform_animation.to(
+                arrow_transform(0.0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
         }
-        if new_over_state.contains(&(AppEvent::ApplyConfig, 0)) {
+        if new_over_state.contains(&(AppEvent::ApplyConfig, HitTag::None)) {
             self.apply_config_button_color_animation.to(
                 ColorF::new_u(33, 33, 33, 100),
                 Duration::from_millis(100),
@@ -554,9 +1039,111 @@ impl DocumentTrait for DeviceConfigurator {
                 AnimationCurve::EASE_IN,
             );
         }
+        if new_over_state.contains(&(AppEvent::ResetConfig, HitTag::None)) {
+            self.reset_config_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.reset_config_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
+        if new_over_state.contains(&(AppEvent::CopyMode, HitTag::None)) {
+            self.copy_mode_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.copy_mode_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
+        if new_over_state.contains(&(AppEvent::PasteMode, HitTag::None)) {
+            self.paste_mode_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.paste_mode_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
+        if new_over_state.contains(&(AppEvent::ExportProfile, HitTag::None)) {
+            self.export_profile_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.export_profile_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
+        if new_over_state.contains(&(AppEvent::ImportProfile, HitTag::None)) {
+            self.import_profile_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.import_profile_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
+        if new_over_state.contains(&(AppEvent::ApplyToAllCheckbox, HitTag::None)) {
+            self.apply_to_all_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.apply_to_all_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
+        for (index, parameter) in self.parameter_vec.iter_mut().enumerate() {
+            if new_over_state.contains(&(AppEvent::Parameter, HitTag::Index(index as u16))) {
+                parameter.over_color_animation.to(
+                    ColorF::new_u(33, 33, 33, 100),
+                    Duration::from_millis(100),
+                    AnimationCurve::EASE_OUT,
+                );
+            } else {
+                parameter.over_color_animation.to(
+                    ColorF::new_u(33, 33, 33, 0),
+                    Duration::from_millis(100),
+                    AnimationCurve::EASE_IN,
+                );
+            }
+        }
     }
 
     fn update_app_state(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        // hide the message after a while
+        if let Some((_, _, instant)) = self.message_option {
+            if instant.elapsed() > Duration::from_secs(5) {
+                self.message_option = None;
+
+                wrapper.global_state.request_redraw();
+            }
+        }
+
         // add mode to the vec
         if self.mode_vec.is_empty() {
             if let (Some(selected_device_config), Some(devide_id)) = (
@@ -579,6 +1166,8 @@ impl DocumentTrait for DeviceConfigurator {
                 {
                     let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
 
+                    self.applied_config_option = Some(selected_device_config.clone());
+
                     // mode
                     for i in 0..driver.driver_configuration_descriptor.mode_count {
                         self.mode_vec.push(Mode {
@@ -599,6 +1188,18 @@ impl DocumentTrait for DeviceConfigurator {
                         });
                     }
 
+                    // reopen on the mode used last time
+                    let last_mode = wrapper
+                        .global_state
+                        .ui_state_manager_mutex
+                        .lock_poisoned()
+                        .config
+                        .last_mode;
+
+                    if last_mode < self.mode_vec.len() {
+                        self.current_mode = last_mode;
+                    }
+
                     // parameters
                     for (index, button_name) in driver
                         .driver_configuration_descriptor
@@ -621,17 +1222,48 @@ impl DocumentTrait for DeviceConfigurator {
                                 ColorF::WHITE,
                                 17.0,
                             ),
+                            is_valid: true,
+                            over_color_animation: Animation::new(
+                                ColorF::new_u(33, 33, 33, 0),
+                                |from: &ColorF, to: &ColorF, value: &mut ColorF, coef: f64| {
+                                    value.a = (to.a - from.a) * coef as f32 + from.a
+                                },
+                            ),
+                            over_color_key: wrapper
+                                .api_mutex
+                                .lock_poisoned()
+                                .generate_property_binding_key(),
                         });
                     }
 
                     wrapper.global_state.request_redraw();
                 }
             }
+
+            // the received config may already contain malformed values
+            self.validate_parameters(&wrapper.global_state.selected_device_config_option_mutex);
+        }
+    }
+
+    fn animate(&mut self, txn: &mut Transaction, _wrapper: &mut WindowWrapper<GlobalState>) {
+        let mut transforms = vec![];
+        let mut colors = vec![];
+
+        if self
+            .mode_selector_previous_arrow_transform_animation
+            .update()
+        {
+            transforms.push(PropertyValue {
+                key: self.mode_selector_previous_arrow_transform_key,
+                value: self.mode_selector_previous_arrow_transform_animation.value,
+            });
+        }
+        if self.mode_selector_next_arrow_transform_animation.update() {
+            transforms.push(PropertyValue {
+                key: self.mode_selector_next_arrow_transform_key,
+                value: self.mode_selector_next_arrow_transform_animation.value,
+            });
         }
-    }
-
-    fn animate(&mut self, txn: &mut Transaction, _wrapper: &mut WindowWrapper<GlobalState>) {
-        let mut colors = vec![];
 
         if self.mode_selector_previous_button_color_animation.update() {
             colors.push(PropertyValue {
@@ -651,19 +1283,59 @@ impl DocumentTrait for DeviceConfigurator {
                 value: self.apply_config_button_color_animation.value,
             });
         }
+        if self.reset_config_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.reset_config_button_color_key,
+                value: self.reset_config_button_color_animation.value,
+            });
+        }
+        if self.copy_mode_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.copy_mode_button_color_key,
+                value: self.copy_mode_button_color_animation.value,
+            });
+        }
+        if self.paste_mode_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.paste_mode_button_color_key,
+                value: self.paste_mode_button_color_animation.value,
+            });
+        }
+        if self.export_profile_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.export_profile_button_color_key,
+                value: self.export_profile_button_color_animation.value,
+            });
+        }
+        if self.import_profile_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.import_profile_button_color_key,
+                value: self.import_profile_button_color_animation.value,
+            });
+        }
+        if self.apply_to_all_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.apply_to_all_button_color_key,
+                value: self.apply_to_all_button_color_animation.value,
+            });
+        }
 
         // parameters
-        for property_value in self
-            .parameter_vec
-            .iter_mut()
-            .filter_map(|parameter| parameter.value.animate())
-        {
-            colors.push(property_value);
+        for parameter in self.parameter_vec.iter_mut() {
+            if let Some(property_value) = parameter.value.animate() {
+                colors.push(property_value);
+            }
+            if parameter.over_color_animation.update() {
+                colors.push(PropertyValue {
+                    key: parameter.over_color_key,
+                    value: parameter.over_color_animation.value,
+                });
+            }
         }
 
-        if !colors.is_empty() {
+        if !transforms.is_empty() || !colors.is_empty() {
             txn.append_dynamic_properties(DynamicProperties {
-                transforms: vec![],
+                transforms,
                 floats: vec![],
                 colors,
             });
@@ -676,15 +1348,46 @@ impl DocumentTrait for DeviceConfigurator {
         _wrapper: &mut WindowWrapper<GlobalState>,
     ) -> LayoutSize {
         let mut height = 25.0;
-        let mut width = self.device_info_text.size.width + self.apply_config_text.size.width + 50.0;
+        let mut width = self.device_info_text.size.width
+            + self
+                .apply_config_text
+                .size
+                .width
+                .max(self.confirm_apply_config_text.size.width)
+            + 50.0;
 
         if !self.mode_vec.is_empty() {
             height += 25.0;
-            width += 210.0;
+            width += 210.0
+                + self
+                    .reset_config_text
+                    .size
+                    .width
+                    .max(self.reset_all_config_text.size.width)
+                + self.copy_mode_text.size.width
+                + self.paste_mode_text.size.width
+                + self.export_profile_text.size.width
+                + self.import_profile_text.size.width
+                + self.apply_to_all_text.size.width
+                + 205.0;
+
+            if let Some((message_text, _, _)) = &self.message_option {
+                width += message_text.size.width + 10.0;
+            }
 
             // parameters
             for parameter in self.parameter_vec.iter() {
-                width = width.max(parameter.name.size.width + parameter.value.width + 30.0);
+                width = width.max(
+                    parameter.name.size.width
+                        + parameter.value.width
+                        + self.reset_parameter_text.size.width
+                        + self
+                            .capture_parameter_text
+                            .size
+                            .width
+                            .max(self.capturing_parameter_text.size.width)
+                        + 80.0,
+                );
             }
 
             height += 35.0 * (self.parameter_vec.len() - 1) as f32 + 30.0;
@@ -725,6 +1428,15 @@ impl DocumentTrait for DeviceConfigurator {
             None,
         );
 
+        // clicking the device informations copies the serial number
+        builder.push_hit_test(
+            device_info_layout_rect,
+            space_and_clip.clip_chain_id,
+            space_and_clip.spatial_id,
+            PrimitiveFlags::empty(),
+            AppEvent::CopySerialNumber.tag(HitTag::None),
+        );
+
         if !self.mode_vec.is_empty() {
             let current_mode = &self.mode_vec[self.current_mode];
 
@@ -733,194 +1445,524 @@ impl DocumentTrait for DeviceConfigurator {
                 LayoutPoint::new(device_info_layout_rect.width() + 10.0, 0.0),
                 LayoutSize::new(200.0, 25.0),
             );
-            let mode_selector_common_item_properties =
-                &CommonItemProperties::new(mode_selector_layout_rect, space_and_clip);
+            let mode_selector_common_item_properties =
+                &CommonItemProperties::new(mode_selector_layout_rect, space_and_clip);
+
+            builder.push_rounded_rect(
+                &mode_selector_common_item_properties,
+                ColorF::new_u(66, 66, 66, 100),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+
+            // mode selector text
+            current_mode.name.push_text_aligned(
+                builder,
+                space_and_clip,
+                LayoutPoint::new(mode_selector_layout_rect.x_range().start + 35.0, 4.0),
+                mode_selector_layout_rect.width() - 35.0 * 2.0,
+                TextAlignment::Center,
+                ColorF::WHITE,
+                None,
+            );
+
+            // mode selector previous
+            let mode_selector_previous_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(mode_selector_layout_rect.x_range().start, 0.0),
+                LayoutSize::new(35.0, 25.0),
+            );
+            let mode_selector_previous_button_common_item_properties = &CommonItemProperties::new(
+                mode_selector_previous_button_layout_rect,
+                space_and_clip,
+            );
+
+            builder.push_rounded_rect_with_animation(
+                &mode_selector_previous_button_common_item_properties,
+                PropertyBinding::Binding(
+                    self.mode_selector_previous_button_color_key,
+                    self.mode_selector_previous_button_color_animation.value,
+                ),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+            builder.push_hit_test(
+                mode_selector_previous_button_layout_rect,
+                space_and_clip.clip_chain_id,
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                AppEvent::ModeSelectorPrevious.tag(HitTag::None),
+            );
+
+            // mode selector next
+            let mode_selector_next_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(mode_selector_layout_rect.x_range().end - 35.0, 0.0),
+                LayoutSize::new(35.0, 25.0),
+            );
+            let mode_selector_next_button_common_item_properties =
+                &CommonItemProperties::new(mode_selector_next_button_layout_rect, space_and_clip);
+
+            builder.push_rounded_rect_with_animation(
+                &mode_selector_next_button_common_item_properties,
+                PropertyBinding::Binding(
+                    self.mode_selector_next_button_color_key,
+                    self.mode_selector_next_button_color_animation.value,
+                ),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+            builder.push_hit_test(
+                mode_selector_next_button_layout_rect,
+                space_and_clip.clip_chain_id,
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                AppEvent::ModeSelectorNext.tag(HitTag::None),
+            );
+
+            // mode selector arrows
+            let white_border_side = BorderSide {
+                color: ColorF::WHITE,
+                style: BorderStyle::Solid,
+            };
+            let transparent_border_side = BorderSide {
+                color: ColorF::TRANSPARENT,
+                style: BorderStyle::Solid,
+            };
+            let red_border_side = BorderSide {
+                color: ColorF::new_u(231, 76, 60, 255),
+                style: BorderStyle::Solid,
+            };
+            let spatial_id = builder.push_reference_frame(
+                LayoutPoint::new(mode_selector_layout_rect.x_range().start, 12.5),
+                space_and_clip.spatial_id,
+                TransformStyle::Flat,
+                PropertyBinding::Binding(
+                    self.mode_selector_previous_arrow_transform_key,
+                    self.mode_selector_previous_arrow_transform_animation.value,
+                ),
+                ReferenceFrameKind::Transform {
+                    is_2d_scale_translation: false,
+                    should_snap: false,
+                    paired_with_perspective: false,
+                },
+                SpatialTreeItemKey::new(2, 0),
+            );
+            let mode_selector_left_arrow_layout_rect =
+                LayoutRect::from_origin_and_size(LayoutPoint::splat(8.5), LayoutSize::splat(10.0));
+            let mode_selector_left_arrow_common_item_properties = &CommonItemProperties::new(
+                mode_selector_left_arrow_layout_rect,
+                SpaceAndClipInfo {
+                    spatial_id,
+                    clip_chain_id: space_and_clip.clip_chain_id,
+                },
+            );
+
+            builder.push_border(
+                mode_selector_left_arrow_common_item_properties,
+                mode_selector_left_arrow_layout_rect,
+                LayoutSideOffsets::new_all_same(1.0),
+                BorderDetails::Normal(NormalBorder {
+                    left: white_border_side,
+                    right: transparent_border_side,
+                    top: white_border_side,
+                    bottom: transparent_border_side,
+                    radius: BorderRadius::zero(),
+                    do_aa: false,
+                }),
+            );
+            builder.pop_reference_frame();
+
+            let spatial_id = builder.push_reference_frame(
+                LayoutPoint::new(mode_selector_layout_rect.x_range().start, 12.5),
+                space_and_clip.spatial_id,
+                TransformStyle::Flat,
+                PropertyBinding::Binding(
+                    self.mode_selector_next_arrow_transform_key,
+                    self.mode_selector_next_arrow_transform_animation.value,
+                ),
+                ReferenceFrameKind::Transform {
+                    is_2d_scale_translation: false,
+                    should_snap: false,
+                    paired_with_perspective: false,
+                },
+                SpatialTreeItemKey::new(2, 1),
+            );
+            let mode_selector_right_arrow_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::splat(123.0),
+                LayoutSize::splat(10.0),
+            );
+            let mode_selector_right_arrow_common_item_properties = &CommonItemProperties::new(
+                mode_selector_right_arrow_layout_rect,
+                SpaceAndClipInfo {
+                    spatial_id,
+                    clip_chain_id: space_and_clip.clip_chain_id,
+                },
+            );
+
+            builder.push_border(
+                mode_selector_right_arrow_common_item_properties,
+                mode_selector_right_arrow_layout_rect,
+                LayoutSideOffsets::new_all_same(1.0),
+                BorderDetails::Normal(NormalBorder {
+                    left: transparent_border_side,
+                    right: white_border_side,
+                    top: transparent_border_side,
+                    bottom: white_border_side,
+                    radius: BorderRadius::zero(),
+                    do_aa: false,
+                }),
+            );
+            builder.pop_reference_frame();
+
+            // apply config button
+            let apply_config_text = if self.apply_to_all_armed {
+                &self.confirm_apply_config_text
+            } else {
+                &self.apply_config_text
+            };
+            let apply_config_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(mode_selector_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(apply_config_text.size.width + 20.0, 25.0),
+            );
+            let apply_config_button_common_item_properties =
+                &CommonItemProperties::new(apply_config_button_layout_rect, space_and_clip);
+
+            builder.push_rounded_rect(
+                &apply_config_button_common_item_properties,
+                ColorF::new_u(66, 66, 66, 100),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+            builder.push_rounded_rect_with_animation(
+                &apply_config_button_common_item_properties,
+                PropertyBinding::Binding(
+                    self.apply_config_button_color_key,
+                    self.apply_config_button_color_animation.value,
+                ),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+
+            apply_config_text.push_text(
+                builder,
+                space_and_clip,
+                LayoutPoint::new(mode_selector_layout_rect.x_range().end + 20.0, 4.0),
+                // greyed out while a parameter is invalid
+                if self.config_valid {
+                    ColorF::WHITE
+                } else {
+                    ColorF::new_u(120, 120, 120, 255)
+                },
+                None,
+            );
+
+            // unsaved changes indicator
+            if self.dirty {
+                let dirty_indicator_layout_rect = LayoutRect::from_origin_and_size(
+                    LayoutPoint::new(apply_config_button_layout_rect.x_range().end - 8.0, 2.0),
+                    LayoutSize::splat(6.0),
+                );
+
+                builder.push_rounded_rect(
+                    &CommonItemProperties::new(dirty_indicator_layout_rect, space_and_clip),
+                    ColorF::new_u(230, 126, 34, 255),
+                    BorderRadius::uniform(3.0),
+                    ClipMode::Clip,
+                );
+            }
+
+            if self.config_valid {
+                builder.push_hit_test(
+                    apply_config_button_layout_rect,
+                    space_and_clip.clip_chain_id,
+                    space_and_clip.spatial_id,
+                    PrimitiveFlags::empty(),
+                    AppEvent::ApplyConfig.tag(HitTag::None),
+                );
+            }
+
+            // reset config button, shift resets every mode
+            let reset_config_text = if self.shift_pressed {
+                &self.reset_all_config_text
+            } else {
+                &self.reset_config_text
+            };
+            let reset_config_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(apply_config_button_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(reset_config_text.size.width + 20.0, 25.0),
+            );
+            let reset_config_button_common_item_properties =
+                &CommonItemProperties::new(reset_config_button_layout_rect, space_and_clip);
+
+            builder.push_rounded_rect(
+                &reset_config_button_common_item_properties,
+                ColorF::new_u(66, 66, 66, 100),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+            builder.push_rounded_rect_with_animation(
+                &reset_config_button_common_item_properties,
+                PropertyBinding::Binding(
+                    self.reset_config_button_color_key,
+                    self.reset_config_button_color_animation.value,
+                ),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+            reset_config_text.push_text(
+                builder,
+                space_and_clip,
+                reset_config_button_layout_rect.min + LayoutSize::new(10.0, 4.0),
+                ColorF::WHITE,
+                None,
+            );
+            builder.push_hit_test(
+                reset_config_button_layout_rect,
+                space_and_clip.clip_chain_id,
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                AppEvent::ResetConfig.tag(HitTag::None),
+            );
+
+            // copy mode button
+            let copy_mode_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(reset_config_button_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(self.copy_mode_text.size.width + 20.0, 25.0),
+            );
+            let copy_mode_button_common_item_properties =
+                &CommonItemProperties::new(copy_mode_button_layout_rect, space_and_clip);
 
             builder.push_rounded_rect(
-                &mode_selector_common_item_properties,
+                &copy_mode_button_common_item_properties,
                 ColorF::new_u(66, 66, 66, 100),
                 BorderRadius::uniform(3.0),
                 ClipMode::Clip,
             );
-
-            // mode selector text
-            current_mode.name.push_text(
+            builder.push_rounded_rect_with_animation(
+                &copy_mode_button_common_item_properties,
+                PropertyBinding::Binding(
+                    self.copy_mode_button_color_key,
+                    self.copy_mode_button_color_animation.value,
+                ),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
+            self.copy_mode_text.push_text(
                 builder,
                 space_and_clip,
-                LayoutPoint::new(mode_selector_layout_rect.x_range().start + 35.0 + 10.0, 4.0),
+                copy_mode_button_layout_rect.min + LayoutSize::new(10.0, 4.0),
                 ColorF::WHITE,
                 None,
             );
-
-            // mode selector previous
-            let mode_selector_previous_button_layout_rect = LayoutRect::from_origin_and_size(
-                LayoutPoint::new(mode_selector_layout_rect.x_range().start, 0.0),
-                LayoutSize::new(35.0, 25.0),
+            builder.push_hit_test(
+                copy_mode_button_layout_rect,
+                space_and_clip.clip_chain_id,
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                AppEvent::CopyMode.tag(HitTag::None),
             );
-            let mode_selector_previous_button_common_item_properties = &CommonItemProperties::new(
-                mode_selector_previous_button_layout_rect,
-                space_and_clip,
+
+            // paste mode button, disabled until a mode is copied
+            let paste_mode_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(copy_mode_button_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(self.paste_mode_text.size.width + 20.0, 25.0),
             );
+            let paste_mode_button_common_item_properties =
+                &CommonItemProperties::new(paste_mode_button_layout_rect, space_and_clip);
 
+            builder.push_rounded_rect(
+                &paste_mode_button_common_item_properties,
+                ColorF::new_u(66, 66, 66, 100),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
             builder.push_rounded_rect_with_animation(
-                &mode_selector_previous_button_common_item_properties,
+                &paste_mode_button_common_item_properties,
                 PropertyBinding::Binding(
-                    self.mode_selector_previous_button_color_key,
-                    self.mode_selector_previous_button_color_animation.value,
+                    self.paste_mode_button_color_key,
+                    self.paste_mode_button_color_animation.value,
                 ),
                 BorderRadius::uniform(3.0),
                 ClipMode::Clip,
             );
-            builder.push_hit_test(
-                mode_selector_previous_button_layout_rect,
-                space_and_clip.clip_chain_id,
-                space_and_clip.spatial_id,
-                PrimitiveFlags::empty(),
-                (AppEvent::ModeSelectorPrevious.into(), 0),
+            self.paste_mode_text.push_text(
+                builder,
+                space_and_clip,
+                paste_mode_button_layout_rect.min + LayoutSize::new(10.0, 4.0),
+                if self.copied_mode_option.is_some() {
+                    ColorF::WHITE
+                } else {
+                    ColorF::new_u(120, 120, 120, 255)
+                },
+                None,
             );
 
-            // mode selector next
-            let mode_selector_next_button_layout_rect = LayoutRect::from_origin_and_size(
-                LayoutPoint::new(mode_selector_layout_rect.x_range().end - 35.0, 0.0),
-                LayoutSize::new(35.0, 25.0),
+            if self.copied_mode_option.is_some() {
+                builder.push_hit_test(
+                    paste_mode_button_layout_rect,
+                    space_and_clip.clip_chain_id,
+                    space_and_clip.spatial_id,
+                    PrimitiveFlags::empty(),
+                    AppEvent::PasteMode.tag(HitTag::None),
+                );
+            }
+
+            // export and import profile buttons
+            let export_profile_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(paste_mode_button_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(self.export_profile_text.size.width + 20.0, 25.0),
             );
-            let mode_selector_next_button_common_item_properties =
-                &CommonItemProperties::new(mode_selector_next_button_layout_rect, space_and_clip);
+            let export_profile_button_common_item_properties =
+                &CommonItemProperties::new(export_profile_button_layout_rect, space_and_clip);
 
+            builder.push_rounded_rect(
+                &export_profile_button_common_item_properties,
+                ColorF::new_u(66, 66, 66, 100),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
+            );
             builder.push_rounded_rect_with_animation(
-                &mode_selector_next_button_common_item_properties,
+                &export_profile_button_common_item_properties,
                 PropertyBinding::Binding(
-                    self.mode_selector_next_button_color_key,
-                    self.mode_selector_next_button_color_animation.value,
+                    self.export_profile_button_color_key,
+                    self.export_profile_button_color_animation.value,
                 ),
                 BorderRadius::uniform(3.0),
                 ClipMode::Clip,
             );
+            self.export_profile_text.push_text(
+                builder,
+                space_and_clip,
+                export_profile_button_layout_rect.min + LayoutSize::new(10.0, 4.0),
+                ColorF::WHITE,
+                None,
+            );
             builder.push_hit_test(
-                mode_selector_next_button_layout_rect,
+                export_profile_button_layout_rect,
                 space_and_clip.clip_chain_id,
                 space_and_clip.spatial_id,
                 PrimitiveFlags::empty(),
-                (AppEvent::ModeSelectorNext.into(), 0),
+                AppEvent::ExportProfile.tag(HitTag::None),
             );
 
-            // mode selector arrows
-            let spatial_id = builder.push_reference_frame(
-                LayoutPoint::new(mode_selector_layout_rect.x_range().start, 12.5),
-                space_and_clip.spatial_id,
-                TransformStyle::Flat,
-                PropertyBinding::Value(LayoutTransform::rotation(
-                    0.0,
-                    0.0,
-                    1.0,
-                    Angle::degrees(-45.0),
-                )),
-                ReferenceFrameKind::Transform {
-                    is_2d_scale_translation: false,
-                    should_snap: false,
-                    paired_with_perspective: false,
-                },
-                SpatialTreeItemKey::new(2, 0),
-            );
-            let white_border_side = BorderSide {
-                color: ColorF::WHITE,
-                style: BorderStyle::Solid,
-            };
-            let transparent_border_side = BorderSide {
-                color: ColorF::TRANSPARENT,
-                style: BorderStyle::Solid,
-            };
-            let mode_selector_left_arrow_layout_rect =
-                LayoutRect::from_origin_and_size(LayoutPoint::splat(8.5), LayoutSize::splat(10.0));
-            let mode_selector_left_arrow_common_item_properties = &CommonItemProperties::new(
-                mode_selector_left_arrow_layout_rect,
-                SpaceAndClipInfo {
-                    spatial_id,
-                    clip_chain_id: space_and_clip.clip_chain_id,
-                },
+            let import_profile_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(export_profile_button_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(self.import_profile_text.size.width + 20.0, 25.0),
             );
+            let import_profile_button_common_item_properties =
+                &CommonItemProperties::new(import_profile_button_layout_rect, space_and_clip);
 
-            builder.push_border(
-                mode_selector_left_arrow_common_item_properties,
-                mode_selector_left_arrow_layout_rect,
-                LayoutSideOffsets::new_all_same(1.0),
-                BorderDetails::Normal(NormalBorder {
-                    left: white_border_side,
-                    right: transparent_border_side,
-                    top: white_border_side,
-                    bottom: transparent_border_side,
-                    radius: BorderRadius::zero(),
-                    do_aa: false,
-                }),
+            builder.push_rounded_rect(
+                &import_profile_button_common_item_properties,
+                ColorF::new_u(66, 66, 66, 100),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
             );
-
-            let mode_selector_right_arrow_layout_rect = LayoutRect::from_origin_and_size(
-                LayoutPoint::splat(123.0),
-                LayoutSize::splat(10.0),
+            builder.push_rounded_rect_with_animation(
+                &import_profile_button_common_item_properties,
+                PropertyBinding::Binding(
+                    self.import_profile_button_color_key,
+                    self.import_profile_button_color_animation.value,
+                ),
+                BorderRadius::uniform(3.0),
+                ClipMode::Clip,
             );
-            let mode_selector_right_arrow_common_item_properties = &CommonItemProperties::new(
-                mode_selector_right_arrow_layout_rect,
-                SpaceAndClipInfo {
-                    spatial_id,
-                    clip_chain_id: space_and_clip.clip_chain_id,
-                },
+            self.import_profile_text.push_text(
+                builder,
+                space_and_clip,
+                import_profile_button_layout_rect.min + LayoutSize::new(10.0, 4.0),
+                ColorF::WHITE,
+                None,
             );
-
-            builder.push_border(
-                mode_selector_right_arrow_common_item_properties,
-                mode_selector_right_arrow_layout_rect,
-                LayoutSideOffsets::new_all_same(1.0),
-                BorderDetails::Normal(NormalBorder {
-                    left: transparent_border_side,
-                    right: white_border_side,
-                    top: transparent_border_side,
-                    bottom: white_border_side,
-                    radius: BorderRadius::zero(),
-                    do_aa: false,
-                }),
+            builder.push_hit_test(
+                import_profile_button_layout_rect,
+                space_and_clip.clip_chain_id,
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                AppEvent::ImportProfile.tag(HitTag::None),
             );
-            builder.pop_reference_frame();
 
-            // apply config button
-            let apply_config_button_layout_rect = LayoutRect::from_origin_and_size(
-                LayoutPoint::new(mode_selector_layout_rect.x_range().end + 10.0, 0.0),
-                LayoutSize::new(self.apply_config_text.size.width + 20.0, 25.0),
+            // apply to all checkbox
+            let apply_to_all_button_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(import_profile_button_layout_rect.x_range().end + 10.0, 0.0),
+                LayoutSize::new(self.apply_to_all_text.size.width + 45.0, 25.0),
             );
-            let apply_config_button_common_item_properties =
-                &CommonItemProperties::new(apply_config_button_layout_rect, space_and_clip);
+            let apply_to_all_button_common_item_properties =
+                &CommonItemProperties::new(apply_to_all_button_layout_rect, space_and_clip);
 
             builder.push_rounded_rect(
-                &apply_config_button_common_item_properties,
+                &apply_to_all_button_common_item_properties,
                 ColorF::new_u(66, 66, 66, 100),
                 BorderRadius::uniform(3.0),
                 ClipMode::Clip,
             );
             builder.push_rounded_rect_with_animation(
-                &apply_config_button_common_item_properties,
+                &apply_to_all_button_common_item_properties,
                 PropertyBinding::Binding(
-                    self.apply_config_button_color_key,
-                    self.apply_config_button_color_animation.value,
+                    self.apply_to_all_button_color_key,
+                    self.apply_to_all_button_color_animation.value,
                 ),
                 BorderRadius::uniform(3.0),
                 ClipMode::Clip,
             );
 
-            self.apply_config_text.push_text(
+            let checkbox_layout_rect = LayoutRect::from_origin_and_size(
+                LayoutPoint::new(apply_to_all_button_layout_rect.x_range().start + 10.0, 6.5),
+                LayoutSize::splat(12.0),
+            );
+
+            builder.push_border(
+                &CommonItemProperties::new(checkbox_layout_rect, space_and_clip),
+                checkbox_layout_rect,
+                LayoutSideOffsets::new_all_same(1.0),
+                BorderDetails::Normal(NormalBorder {
+                    left: white_border_side,
+                    right: white_border_side,
+                    top: white_border_side,
+                    bottom: white_border_side,
+                    radius: BorderRadius::uniform(2.0),
+                    do_aa: true,
+                }),
+            );
+
+            if self.apply_to_all {
+                builder.push_rounded_rect(
+                    &CommonItemProperties::new(
+                        checkbox_layout_rect.inflate(-3.0, -3.0),
+                        space_and_clip,
+                    ),
+                    ColorF::WHITE,
+                    BorderRadius::uniform(1.0),
+                    ClipMode::Clip,
+                );
+            }
+
+            self.apply_to_all_text.push_text(
                 builder,
                 space_and_clip,
-                LayoutPoint::new(mode_selector_layout_rect.x_range().end + 20.0, 4.0),
+                LayoutPoint::new(checkbox_layout_rect.x_range().end + 8.0, 4.0),
                 ColorF::WHITE,
                 None,
             );
 
             builder.push_hit_test(
-                apply_config_button_layout_rect,
+                apply_to_all_button_layout_rect,
                 space_and_clip.clip_chain_id,
                 space_and_clip.spatial_id,
                 PrimitiveFlags::empty(),
-                (AppEvent::ApplyConfig.into(), 0),
+                AppEvent::ApplyToAllCheckbox.tag(HitTag::None),
             );
 
+            // message
+            if let Some((message_text, message_color, _)) = &self.message_option {
+                message_text.push_text(
+                    builder,
+                    space_and_clip,
+                    LayoutPoint::new(apply_to_all_button_layout_rect.x_range().end + 10.0, 4.0),
+                    *message_color,
+                    None,
+                );
+            }
+
             // parameters
             let mut parameter_position = LayoutPoint::new(10.0, 45.0);
 
@@ -941,12 +1983,62 @@ impl DocumentTrait for DeviceConfigurator {
                     BorderRadius::uniform(3.0),
                     ClipMode::Clip,
                 );
+                builder.push_rounded_rect_with_animation(
+                    &parameter_common_item_properties,
+                    PropertyBinding::Binding(
+                        parameter.over_color_key,
+                        parameter.over_color_animation.value,
+                    ),
+                    BorderRadius::uniform(3.0),
+                    ClipMode::Clip,
+                );
+
+                // focus highlight
+                if self.apply_configcurrent_focused_parameter_index_option == Some(index) {
+                    builder.push_rounded_rect(
+                        &parameter_common_item_properties,
+                        ColorF::new_u(255, 255, 255, 15),
+                        BorderRadius::uniform(3.0),
+                        ClipMode::Clip,
+                    );
+                    builder.push_border(
+                        &parameter_common_item_properties,
+                        parameter_layout_rect,
+                        LayoutSideOffsets::new_all_same(1.0),
+                        BorderDetails::Normal(NormalBorder {
+                            left: white_border_side,
+                            right: white_border_side,
+                            top: white_border_side,
+                            bottom: white_border_side,
+                            radius: BorderRadius::uniform(3.0),
+                            do_aa: true,
+                        }),
+                    );
+                }
+
+                // rejected by the tokenizer
+                if !parameter.is_valid {
+                    builder.push_border(
+                        &parameter_common_item_properties,
+                        parameter_layout_rect,
+                        LayoutSideOffsets::new_all_same(1.0),
+                        BorderDetails::Normal(NormalBorder {
+                            left: red_border_side,
+                            right: red_border_side,
+                            top: red_border_side,
+                            bottom: red_border_side,
+                            radius: BorderRadius::uniform(3.0),
+                            do_aa: true,
+                        }),
+                    );
+                }
+
                 builder.push_hit_test(
                     parameter_layout_rect,
                     space_and_clip.clip_chain_id,
                     space_and_clip.spatial_id,
                     PrimitiveFlags::empty(),
-                    (AppEvent::Parameter.into(), index as u16),
+                    AppEvent::Parameter.tag(HitTag::Index(index as u16)),
                 );
                 parameter.name.push_text(
                     builder,
@@ -963,8 +2055,201 @@ impl DocumentTrait for DeviceConfigurator {
                     None,
                 );
 
+                // reset parameter button
+                let reset_parameter_layout_rect = LayoutRect::from_origin_and_size(
+                    LayoutPoint::new(
+                        parameter_layout_rect.x_range().end + 5.0,
+                        parameter_position.y,
+                    ),
+                    LayoutSize::new(self.reset_parameter_text.size.width + 20.0, 25.0),
+                );
+
+                builder.push_rounded_rect(
+                    &CommonItemProperties::new(reset_parameter_layout_rect, space_and_clip),
+                    ColorF::new_u(66, 66, 66, 100),
+                    BorderRadius::uniform(3.0),
+                    ClipMode::Clip,
+                );
+                builder.push_hit_test(
+                    reset_parameter_layout_rect,
+                    space_and_clip.clip_chain_id,
+                    space_and_clip.spatial_id,
+                    PrimitiveFlags::empty(),
+                    AppEvent::ResetParameter.tag(HitTag::Index(index as u16)),
+                );
+                self.reset_parameter_text.push_text(
+                    builder,
+                    space_and_clip,
+                    reset_parameter_layout_rect.min + LayoutSize::new(10.0, 4.0),
+                    ColorF::WHITE,
+                    None,
+                );
+
+                // capture parameter button
+                let capture_parameter_text = if self.capturing_parameter_index_option == Some(index)
+                {
+                    &self.capturing_parameter_text
+                } else {
+                    &self.capture_parameter_text
+                };
+                let capture_parameter_layout_rect = LayoutRect::from_origin_and_size(
+                    LayoutPoint::new(
+                        reset_parameter_layout_rect.x_range().end + 5.0,
+                        parameter_position.y,
+                    ),
+                    LayoutSize::new(capture_parameter_text.size.width + 20.0, 25.0),
+                );
+
+                builder.push_rounded_rect(
+                    &CommonItemProperties::new(capture_parameter_layout_rect, space_and_clip),
+                    ColorF::new_u(66, 66, 66, 100),
+                    BorderRadius::uniform(3.0),
+                    ClipMode::Clip,
+                );
+                builder.push_hit_test(
+                    capture_parameter_layout_rect,
+                    space_and_clip.clip_chain_id,
+                    space_and_clip.spatial_id,
+                    PrimitiveFlags::empty(),
+                    AppEvent::CaptureParameter.tag(HitTag::Index(index as u16)),
+                );
+                capture_parameter_text.push_text(
+                    builder,
+                    space_and_clip,
+                    capture_parameter_layout_rect.min + LayoutSize::new(10.0, 4.0),
+                    ColorF::WHITE,
+                    None,
+                );
+
                 parameter_position += LayoutSize::new(0.0, 35.0);
             }
         }
     }
 }
+
+// the driver tokenizer is the reference, so a value it rejects is never applied
+fn validate_config_string(value: &str) -> bool {
+    tokenize(value).is_ok()
+}
+
+// the mode selector arrows are rotated squares, the offset slides them horizontally
+fn arrow_transform(offset: f32) -> LayoutTransform {
+    LayoutTransform::rotation(0.0, 0.0, 1.0, Angle::degrees(-45.0))
+        .then_translate(LayoutVector3D::new(offset, 0.0, 0.0))
+}
+
+const LETTER_KEYCODES: [VirtualKeyCode; 26] = [
+    VirtualKeyCode::A,
+    VirtualKeyCode::B,
+    VirtualKeyCode::C,
+    VirtualKeyCode::D,
+    VirtualKeyCode::E,
+    VirtualKeyCode::F,
+    VirtualKeyCode::G,
+    VirtualKeyCode::H,
+    VirtualKeyCode::I,
+    VirtualKeyCode::J,
+    VirtualKeyCode::K,
+    VirtualKeyCode::L,
+    VirtualKeyCode::M,
+    VirtualKeyCode::N,
+    VirtualKeyCode::O,
+    VirtualKeyCode::P,
+    VirtualKeyCode::Q,
+    VirtualKeyCode::R,
+    VirtualKeyCode::S,
+    VirtualKeyCode::T,
+    VirtualKeyCode::U,
+    VirtualKeyCode::V,
+    VirtualKeyCode::W,
+    VirtualKeyCode::X,
+    VirtualKeyCode::Y,
+    VirtualKeyCode::Z,
+];
+
+const DIGIT_KEYCODES: [VirtualKeyCode; 10] = [
+    VirtualKeyCode::Key0,
+    VirtualKeyCode::Key1,
+    VirtualKeyCode::Key2,
+    VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4,
+    VirtualKeyCode::Key5,
+    VirtualKeyCode::Key6,
+    VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8,
+    VirtualKeyCode::Key9,
+];
+
+// convert a key combination to tokens like {+CTRL}{+SHIFT}a{-SHIFT}{-CTRL},
+// only keys typing a character are supported, lone modifiers return none
+fn key_combination_to_tokens(keycode: VirtualKeyCode, modifiers: ModifiersState) -> Option<String> {
+    let key = if let Some(index) = LETTER_KEYCODES.iter().position(|key| *key == keycode) {
+        (b'a' + index as u8) as char
+    } else if let Some(index) = DIGIT_KEYCODES.iter().position(|key| *key == keycode) {
+        (b'0' + index as u8) as char
+    } else {
+        match keycode {
+            VirtualKeyCode::Space => ' ',
+            VirtualKeyCode::Minus => '-',
+            VirtualKeyCode::Equals => '=',
+            VirtualKeyCode::Comma => ',',
+            VirtualKeyCode::Period => '.',
+            VirtualKeyCode::Slash => '/',
+            VirtualKeyCode::Semicolon => ';',
+            VirtualKeyCode::Apostrophe => '\'',
+            VirtualKeyCode::LBracket => '[',
+            VirtualKeyCode::RBracket => ']',
+            VirtualKeyCode::Backslash => '\\',
+            VirtualKeyCode::Grave => '`',
+            _ => return None,
+        }
+    };
+    let modifier_vec: Vec<&str> = [
+        (modifiers.ctrl(), "CTRL"),
+        (modifiers.alt(), "ALT"),
+        (modifiers.shift(), "SHIFT"),
+        (modifiers.logo(), "META"),
+    ]
+    .iter()
+    .filter(|(pressed, _)| *pressed)
+    .map(|(_, name)| *name)
+    .collect();
+    let mut tokens = String::new();
+
+    for modifier in modifier_vec.iter() {
+        tokens += &format!("{{+{}}}", modifier);
+    }
+
+    tokens.push(key);
+
+    for modifier in modifier_vec.iter().rev() {
+        tokens += &format!("{{-{}}}", modifier);
+    }
+
+    Some(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_config_string_follows_the_tokenizer() {
+        assert!(validate_config_string(""));
+        assert!(validate_config_string("hello"));
+        assert!(validate_config_string("{+CTRL}c{-CTRL}"));
+        assert!(!validate_config_string("{+CTRL"));
+    }
+
+    #[test]
+    fn captured_key_combinations_are_valid() {
+        let tokens = key_combination_to_tokens(
+            VirtualKeyCode::C,
+            ModifiersState::CTRL | ModifiersState::SHIFT,
+        )
+        .unwrap();
+
+        assert_eq!(tokens, "{+CTRL}{+SHIFT}c{-SHIFT}{-CTRL}");
+        assert!(validate_config_string(&tokens));
+    }
+}
@@ -1,30 +1,87 @@
 use std::net::SocketAddr;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::vec;
 
-use crate::animation::{Animation, AnimationCurve};
+use crate::animation::{Animation, AnimationCurve, RepeatMode};
 use crate::ui::DocumentTrait;
 use crate::window::ext::{ColorFTrait, DisplayListBuilderExt};
-use crate::window::{FrameBuilder, GlobalStateTrait, WindowWrapper};
+use crate::window::{FrameBuilder, GlobalStateTrait, Text, TextAlignment, WindowWrapper};
 use crate::{ConnectionEvent, DeviceId, GlobalState};
 
+use copypasta::{ClipboardContext, ClipboardProvider};
 use hashbrown::{HashMap, HashSet};
 use image::imageops::{resize, FilterType};
 use image::load_from_memory;
+use log::warn;
 use util::thread::MutexTrait;
 use webrender::api::units::{LayoutPoint, LayoutRect, LayoutSize};
 use webrender::api::{
-    AlphaType, BorderRadius, ClipMode, ColorF, CommonItemProperties, DocumentId, DynamicProperties,
-    FilterOp, HitTestResultItem, IdNamespace, ImageData, ImageDescriptor, ImageDescriptorFlags,
-    ImageFormat, ImageKey, ImageRendering, PrimitiveFlags, PropertyBinding, PropertyBindingKey,
-    PropertyValue, SpaceAndClipInfo,
+    AlphaType, BorderRadius, ClipMode, ColorF, CommonItemProperties, DynamicProperties, FilterOp,
+    HitTestResultItem, ImageKey, ImageRendering, PrimitiveFlags, PropertyBinding,
+    PropertyBindingKey, PropertyValue, SpaceAndClipInfo,
 };
-use webrender::{RenderApi, Transaction};
+use webrender::Transaction;
+use winit::event::VirtualKeyCode;
 
 use super::device_configurator::DeviceConfigurator;
-use super::{AppEvent, AppEventType};
+use super::text_input::{TextInput, TextInputEvent};
+use super::{AppEvent, AppEventType, HitTag};
+
+// time given to the last configured device to reconnect on launch
+const AUTO_OPEN_TIMEOUT: Duration = Duration::from_secs(10);
+const DEVICE_BUTTON_SIZE: f32 = 150.0;
+const DEVICE_BUTTON_SPACING: f32 = 10.0;
+const SEARCH_INPUT_HEIGHT: f32 = 25.0;
+const SORT_MODE_BUTTON_WIDTH: f32 = 150.0;
+
+// number of buttons fitting in a row, at least one
+fn column_count(width: f32) -> usize {
+    (((width + DEVICE_BUTTON_SPACING) / (DEVICE_BUTTON_SIZE + DEVICE_BUTTON_SPACING)) as usize)
+        .max(1)
+}
+
+// the buttons are placed under the search input
+fn device_button_position(index: usize, column_count: usize) -> LayoutPoint {
+    LayoutPoint::new(
+        (index % column_count) as f32 * (DEVICE_BUTTON_SIZE + DEVICE_BUTTON_SPACING),
+        (index / column_count) as f32 * (DEVICE_BUTTON_SIZE + DEVICE_BUTTON_SPACING)
+            + SEARCH_INPUT_HEIGHT
+            + DEVICE_BUTTON_SPACING,
+    )
+}
+
+// case insensitive match on the device name or the serial number
+fn matches_search(device_name: &str, serial_number: &str, search: &str) -> bool {
+    let search = search.to_lowercase();
+
+    device_name.to_lowercase().contains(&search) || serial_number.to_lowercase().contains(&search)
+}
+
+#[derive(Clone, Copy)]
+enum SortMode {
+    Name,
+    SerialNumber,
+    RecentlyConnected,
+}
+
+impl SortMode {
+    fn next(self) -> Self {
+        match self {
+            SortMode::Name => SortMode::SerialNumber,
+            SortMode::SerialNumber => SortMode::RecentlyConnected,
+            SortMode::RecentlyConnected => SortMode::Name,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Name => "Sort by name",
+            SortMode::SerialNumber => "Sort by serial number",
+            SortMode::RecentlyConnected => "Sort by recent",
+        }
+    }
+}
 
 pub struct DeviceIcon {
     image_key: ImageKey,
@@ -45,9 +102,11 @@ impl DeviceIcon {
 #[derive(Clone)]
 struct DeviceData {
     to_remove: bool,
+    is_duplicate: bool,
     device_id: DeviceId,
     device_name: String,
     icon_option: Option<Rc<DeviceIcon>>,
+    connection_instant: Instant,
     animation: Animation<f32>,
     over_color_animation: Animation<ColorF>,
     property_key: PropertyBindingKey<f32>,
@@ -59,6 +118,7 @@ impl DeviceData {
         device_id: DeviceId,
         device_name: String,
         icon_option: Option<Rc<DeviceIcon>>,
+        connection_instant: Instant,
         animation: Animation<f32>,
         over_color_animation: Animation<ColorF>,
         property_key: PropertyBindingKey<f32>,
@@ -66,9 +126,11 @@ impl DeviceData {
     ) -> Self {
         Self {
             to_remove: false,
+            is_duplicate: false,
             device_id,
             device_name,
             icon_option,
+            connection_instant,
             animation,
             over_color_animation,
             property_key,
@@ -79,20 +141,142 @@ impl DeviceData {
 
 pub struct DeviceList {
     device_data_vec: Vec<DeviceData>,
-    device_icon_option_hashmap: HashMap<SocketAddr, Option<Rc<DeviceIcon>>>,
-    image_id: u32,
+    // icons are loaded once per driver connection
+    device_icon_option_hashmap: HashMap<SocketAddr, (Instant, Option<Rc<DeviceIcon>>)>,
     device_icon_to_keep_hashset_option: Option<HashSet<SocketAddr>>,
+    clipboard_context_option: Option<ClipboardContext>,
+    copied_device_id_option: Option<(DeviceId, Instant)>,
+    auto_open_serial_number_option: Option<(String, Instant)>,
+    search_text_input: TextInput,
+    // last mouse position in the document, to select text in the search input
+    mouse_position: LayoutPoint,
+    sort_mode: SortMode,
+    sort_mode_text: Text,
+    sort_mode_button_color_key: PropertyBindingKey<ColorF>,
+    sort_mode_button_color_animation: Animation<ColorF>,
+    // pulses while no device is connected
+    searching_text: Text,
+    searching_opacity_key: PropertyBindingKey<f32>,
+    searching_opacity_animation: Animation<f32>,
 }
 
 impl DeviceList {
-    pub fn new() -> Self {
+    pub fn new(wrapper: &mut WindowWrapper<GlobalState>) -> Self {
+        let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
+        let mut searching_opacity_animation =
+            Animation::new(1.0, |from: &f32, to: &f32, value: &mut f32, coef: f64| {
+                *value = (to - from) * coef as f32 + from
+            });
+
+        searching_opacity_animation.to_repeating(
+            0.3,
+            Duration::from_millis(800),
+            AnimationCurve::EASE_IN_OUT,
+            RepeatMode::PingPong,
+        );
+
         Self {
             device_data_vec: Vec::new(),
             device_icon_option_hashmap: HashMap::new(),
-            image_id: 0,
             device_icon_to_keep_hashset_option: None,
+            // copy is disabled when no clipboard backend is available
+            clipboard_context_option: match ClipboardContext::new() {
+                Ok(clipboard_context) => Some(clipboard_context),
+                Err(error) => {
+                    warn!("Failed to initialize the clipboard : {}", error);
+                    None
+                }
+            },
+            copied_device_id_option: None,
+            auto_open_serial_number_option: None,
+            search_text_input: TextInput::new(
+                String::new(),
+                &font_hashmap["OpenSans_13px"],
+                &wrapper.api_mutex,
+                ColorF::WHITE,
+                17.0,
+            ),
+            mouse_position: LayoutPoint::zero(),
+            sort_mode: SortMode::Name,
+            sort_mode_text: font_hashmap["OpenSans_13px"]
+                .create_text(SortMode::Name.label().to_string(), None),
+            sort_mode_button_color_key: wrapper
+                .api_mutex
+                .lock_poisoned()
+                .generate_property_binding_key(),
+            sort_mode_button_color_animation: Animation::new(
+                ColorF::new_u(33, 33, 33, 0),
+                |from: &ColorF, to: &ColorF, value: &mut ColorF, coef: f64| {
+                    value.a = (to.a - from.a) * coef as f32 + from.a
+                },
+            ),
+            searching_text: font_hashmap["OpenSans_13px"]
+                .create_text("Searching for devices…".to_string(), None),
+            searching_opacity_key: wrapper
+                .api_mutex
+                .lock_poisoned()
+                .generate_property_binding_key(),
+            searching_opacity_animation,
+        }
+    }
+
+    // the order is total so the buttons never swap between two frames
+    fn sort_device_data(&mut self) {
+        match self.sort_mode {
+            SortMode::Name => self.device_data_vec.sort_by(|a, b| {
+                a.device_name
+                    .cmp(&b.device_name)
+                    .then_with(|| a.device_id.serial_number.cmp(&b.device_id.serial_number))
+                    .then_with(|| a.device_id.socket_addr.cmp(&b.device_id.socket_addr))
+            }),
+            SortMode::SerialNumber => self.device_data_vec.sort_by(|a, b| {
+                a.device_id
+                    .serial_number
+                    .cmp(&b.device_id.serial_number)
+                    .then_with(|| a.device_name.cmp(&b.device_name))
+                    .then_with(|| a.device_id.socket_addr.cmp(&b.device_id.socket_addr))
+            }),
+            SortMode::RecentlyConnected => self.device_data_vec.sort_by(|a, b| {
+                b.connection_instant
+                    .cmp(&a.connection_instant)
+                    .then_with(|| a.device_name.cmp(&b.device_name))
+                    .then_with(|| a.device_id.serial_number.cmp(&b.device_id.serial_number))
+                    .then_with(|| a.device_id.socket_addr.cmp(&b.device_id.socket_addr))
+            }),
         }
     }
+
+    // open the configurator of this serial number as soon as a driver reports it
+    pub fn with_auto_open(
+        serial_number_option: Option<String>,
+        wrapper: &mut WindowWrapper<GlobalState>,
+    ) -> Self {
+        Self {
+            auto_open_serial_number_option: serial_number_option
+                .map(|serial_number| (serial_number, Instant::now())),
+            ..Self::new(wrapper)
+        }
+    }
+
+    fn open_device(&mut self, device_id: DeviceId, wrapper: &mut WindowWrapper<GlobalState>) {
+        self.auto_open_serial_number_option = None;
+
+        wrapper
+            .global_state
+            .set_last_serial_number(Some(device_id.serial_number.clone()));
+        *wrapper
+            .global_state
+            .selected_device_id_option_mutex
+            .lock_poisoned() = Some(device_id.clone());
+        wrapper
+            .global_state
+            .push_connection_event(ConnectionEvent::RequestDeviceConfig(device_id));
+
+        *wrapper
+            .global_state
+            .new_document_option_mutex
+            .lock_poisoned() = Some(Box::new(DeviceConfigurator::new(wrapper)));
+    }
 }
 
 impl DocumentTrait for DeviceList {
@@ -100,38 +284,119 @@ impl DocumentTrait for DeviceList {
         "Device List"
     }
 
+    fn is_capturing_keys(&self) -> bool {
+        self.search_text_input.focused
+    }
+
     fn calculate_event(
         &mut self,
         hit_items: &Vec<HitTestResultItem>,
         wrapper: &mut WindowWrapper<GlobalState>,
         target_event_type: AppEventType,
     ) {
+        // search input event logic
+        if self.search_text_input.focused {
+            let pressed_search_input = hit_items
+                .first()
+                .and_then(|hit_item| AppEvent::from_tag(hit_item.tag))
+                == Some((AppEvent::SearchInput, HitTag::None));
+
+            match target_event_type {
+                // the press moves the cursor of the search input instead
+                AppEventType::MousePressed if pressed_search_input => {}
+                AppEventType::MouseReleased => self.search_text_input.mouse_released(),
+                AppEventType::MousePressed
+                | AppEventType::Focus(false)
+                | AppEventType::KeyPressed {
+                    keycode: VirtualKeyCode::Escape,
+                    ..
+                } => {
+                    self.search_text_input.set_focus(false);
+
+                    wrapper.global_state.request_redraw();
+                }
+                AppEventType::KeyPressed { keycode, modifiers } => {
+                    if self.search_text_input.key_pressed(
+                        &wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"],
+                        keycode,
+                        modifiers,
+                        self.clipboard_context_option.as_mut(),
+                    ) != TextInputEvent::Ignored
+                    {
+                        wrapper.global_state.request_redraw();
+                    }
+                }
+                AppEventType::Char(char) => {
+                    if self.search_text_input.char_received(
+                        &wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"],
+                        char,
+                    ) != TextInputEvent::Ignored
+                    {
+                        wrapper.global_state.request_redraw();
+                    }
+                }
+                _ => {}
+            }
+        }
+
         if !hit_items.is_empty() {
-            if let Some(event) = AppEvent::from(hit_items[0].tag.0) {
+            if let Some((event, hit_tag)) = AppEvent::from_tag(hit_items[0].tag) {
                 match target_event_type {
+                    AppEventType::MousePressed => {
+                        if let AppEvent::SearchInput = event {
+                            // measured as it was drawn, before focusing adds the cursor gap
+                            self.search_text_input.mouse_pressed(
+                                &wrapper.global_state.font_hashmap_mutex.lock_poisoned()
+                                    ["OpenSans_13px"],
+                                self.mouse_position,
+                                false,
+                            );
+                            self.search_text_input.set_focus(true);
+
+                            wrapper.global_state.request_redraw();
+                        }
+                    }
                     AppEventType::MouseReleased => match event {
+                        AppEvent::SortModeButton => {
+                            self.sort_mode = self.sort_mode.next();
+                            self.sort_mode_text =
+                                wrapper.global_state.font_hashmap_mutex.lock_poisoned()
+                                    ["OpenSans_13px"]
+                                    .create_text(self.sort_mode.label().to_string(), None);
+
+                            wrapper.global_state.request_redraw();
+                        }
                         AppEvent::ChooseDeviceButton => {
+                            let index = match hit_tag {
+                                HitTag::Index(index) => index as usize,
+                                _ => return,
+                            };
+
+                            let device_id =
+                                wrapper.global_state.device_id_vec_mutex.lock_poisoned()[index]
+                                    .clone();
+
+                            self.open_device(device_id, wrapper);
+                        }
+                        AppEvent::CopySerialNumber => {
+                            if let (HitTag::Index(index), Some(clipboard_context)) =
+                                (hit_tag, self.clipboard_context_option.as_mut())
                             {
-                                let device_id_vec =
-                                    wrapper.global_state.device_id_vec_mutex.lock_poisoned();
-                                let mut selected_device_id_option = wrapper
-                                    .global_state
-                                    .selected_device_id_option_mutex
-                                    .lock_poisoned();
-
-                                *selected_device_id_option =
-                                    Some(device_id_vec[hit_items[0].tag.1 as usize].clone());
-                                wrapper.global_state.push_connection_event(
-                                    ConnectionEvent::RequestDeviceConfig(
-                                        device_id_vec[hit_items[0].tag.1 as usize].clone(),
-                                    ),
-                                );
-                            }
+                                let device_id =
+                                    wrapper.global_state.device_id_vec_mutex.lock_poisoned()
+                                        [index as usize]
+                                        .clone();
+
+                                if clipboard_context
+                                    .set_contents(device_id.serial_number.clone())
+                                    .is_ok()
+                                {
+                                    self.copied_device_id_option =
+                                        Some((device_id, Instant::now()));
 
-                            *wrapper
-                                .global_state
-                                .new_document_option_mutex
-                                .lock_poisoned() = Some(Box::new(DeviceConfigurator::new(wrapper)));
+                                    wrapper.global_state.request_redraw();
+                                }
+                            }
                         }
                         _ => {}
                     },
@@ -141,9 +406,39 @@ impl DocumentTrait for DeviceList {
         }
     }
 
-    fn update_over_state(&mut self, new_over_state: &HashSet<(AppEvent, u16)>) {
+    fn mouse_moved(
+        &mut self,
+        mouse_position: LayoutPoint,
+        wrapper: &mut WindowWrapper<GlobalState>,
+    ) {
+        self.mouse_position = mouse_position;
+
+        if self.search_text_input.mouse_moved(
+            &wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"],
+            mouse_position,
+        ) != TextInputEvent::Ignored
+        {
+            wrapper.global_state.request_redraw();
+        }
+    }
+
+    fn update_over_state(&mut self, new_over_state: &HashSet<(AppEvent, HitTag)>) {
+        if new_over_state.contains(&(AppEvent::SortModeButton, HitTag::None)) {
+            self.sort_mode_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 100),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_OUT,
+            );
+        } else {
+            self.sort_mode_button_color_animation.to(
+                ColorF::new_u(33, 33, 33, 0),
+                Duration::from_millis(100),
+                AnimationCurve::EASE_IN,
+            );
+        }
         for (index, device_data) in self.device_data_vec.iter_mut().enumerate() {
-            if new_over_state.contains(&(AppEvent::ChooseDeviceButton, index as u16)) {
+            if new_over_state.contains(&(AppEvent::ChooseDeviceButton, HitTag::Index(index as u16)))
+            {
                 device_data.over_color_animation.to(
                     ColorF::new_u(33, 33, 33, 100),
                     Duration::from_millis(100),
@@ -160,6 +455,41 @@ impl DocumentTrait for DeviceList {
     }
 
     fn update_app_state(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if let Some((serial_number, instant)) = self.auto_open_serial_number_option.clone() {
+            // the device did not reconnect, stay on the list
+            if instant.elapsed() > AUTO_OPEN_TIMEOUT {
+                self.auto_open_serial_number_option = None;
+            } else {
+                let socket_addr_option = wrapper
+                    .global_state
+                    .driver_hashmap_mutex
+                    .lock_poisoned()
+                    .iter()
+                    .find(|(_, driver)| {
+                        driver
+                            .device_list
+                            .serial_number_vec
+                            .contains(&serial_number)
+                    })
+                    .map(|(socket_addr, _)| *socket_addr);
+
+                if let Some(socket_addr) = socket_addr_option {
+                    self.open_device(DeviceId::new(socket_addr, serial_number), wrapper);
+
+                    return;
+                }
+            }
+        }
+
+        // hide the copy confirmation after a while
+        if let Some((_, instant)) = self.copied_device_id_option {
+            if instant.elapsed() > Duration::from_millis(1500) {
+                self.copied_device_id_option = None;
+
+                wrapper.global_state.request_redraw();
+            }
+        }
+
         let drained_device_data_vec: Vec<DeviceData> = self.device_data_vec.drain(..).collect();
         let mut device_icon_to_keep_hashset = HashSet::new();
 
@@ -181,6 +511,23 @@ impl DocumentTrait for DeviceList {
         let mut floats = vec![];
         let mut colors = vec![];
 
+        if let Some(property_value) = self.search_text_input.animate() {
+            colors.push(property_value);
+        }
+        if self.sort_mode_button_color_animation.update() {
+            colors.push(PropertyValue {
+                key: self.sort_mode_button_color_key,
+                value: self.sort_mode_button_color_animation.value,
+            });
+        }
+
+        if self.device_data_vec.is_empty() && self.searching_opacity_animation.update() {
+            floats.push(PropertyValue {
+                key: self.searching_opacity_key,
+                value: self.searching_opacity_animation.value,
+            });
+        }
+
         for device_data in self.device_data_vec.iter_mut() {
             if device_data.animation.update() {
                 floats.push(PropertyValue {
@@ -204,19 +551,19 @@ impl DocumentTrait for DeviceList {
             });
         }
         if let Some(device_icon_to_keep_hashset) = self.device_icon_to_keep_hashset_option.take() {
-            let driver_hashmap = wrapper.global_state.driver_hashmap_mutex.lock_poisoned();
+            let global_state = wrapper.global_state.clone();
+            let driver_hashmap = global_state.driver_hashmap_mutex.lock_poisoned();
 
             // remove unused icon
             for socket_addr in self.device_icon_option_hashmap.clone().keys() {
                 if !device_icon_to_keep_hashset.contains(socket_addr)
                     && !driver_hashmap.contains_key(socket_addr)
                 {
-                    if let Some(device_icon) = self.device_icon_option_hashmap[socket_addr].clone()
+                    if let Some((_, Some(device_icon))) =
+                        self.device_icon_option_hashmap.remove(socket_addr)
                     {
-                        txn.delete_image(device_icon.image_key);
+                        wrapper.unload_image(device_icon.image_key);
                     }
-
-                    self.device_icon_option_hashmap.remove(socket_addr);
                 }
             }
         }
@@ -227,61 +574,90 @@ impl DocumentTrait for DeviceList {
         mut frame_size: LayoutSize,
         wrapper: &mut WindowWrapper<GlobalState>,
     ) -> LayoutSize {
-        let driver_hashmap = wrapper.global_state.driver_hashmap_mutex.lock_poisoned();
-        let mut device_button_layout_point = LayoutPoint::zero();
+        // the global state is cloned so images can be loaded while the drivers are locked
+        let global_state = wrapper.global_state.clone();
+        let driver_hashmap = global_state.driver_hashmap_mutex.lock_poisoned();
         let mut device_data_to_keep_hashset = HashSet::new();
+        let mut serial_number_count_hashmap: HashMap<&String, usize> = HashMap::new();
+
+        // a serial number reported by more than one driver
+        for driver in driver_hashmap.values() {
+            for serial_number in driver.device_list.serial_number_vec.iter() {
+                *serial_number_count_hashmap
+                    .entry(serial_number)
+                    .or_default() += 1;
+            }
+        }
 
         for (socket_addr, driver) in driver_hashmap.iter() {
+            // a reconnected driver can send another icon, the old one is reloaded
+            if let Some((connection_instant, device_icon_option)) =
+                self.device_icon_option_hashmap.get(socket_addr)
+            {
+                if *connection_instant != driver.connection_instant {
+                    if let Some(device_icon) = device_icon_option {
+                        wrapper.unload_image(device_icon.image_key);
+                    }
+
+                    // buttons still fading out must not draw the deleted image
+                    for device_data in self.device_data_vec.iter_mut() {
+                        if device_data.device_id.socket_addr == *socket_addr {
+                            device_data.icon_option = None;
+                        }
+                    }
+
+                    self.device_icon_option_hashmap.remove(socket_addr);
+                }
+            }
+
             // initialize icon if needed
             if let None = self.device_icon_option_hashmap.get(socket_addr) {
-                self.device_icon_option_hashmap.insert(
-                    *socket_addr,
-                    match load_from_memory(
-                        driver
-                            .driver_configuration_descriptor
-                            .device_icon
-                            .as_slice(),
-                    ) {
-                        Ok(image) => {
-                            let mut height = 150.0f32;
-                            let mut width = 150.0f32;
-
-                            if image.height() > image.width() {
-                                width /= image.height() as f32;
-                                width *= image.width() as f32;
-                            } else {
-                                height /= image.width() as f32;
-                                height *= image.height() as f32;
-                            }
+                let device_icon_option = match load_from_memory(
+                    driver
+                        .driver_configuration_descriptor
+                        .device_icon
+                        .as_slice(),
+                ) {
+                    Ok(image) => {
+                        let mut height = 150.0f32;
+                        let mut width = 150.0f32;
 
-                            let image =
-                                resize(&image, width as u32, height as u32, FilterType::Lanczos3);
-                            let image_descriptor = ImageDescriptor::new(
-                                width as i32,
-                                height as i32,
-                                ImageFormat::RGBA8,
-                                ImageDescriptorFlags::empty(),
-                            );
-                            let image_data = ImageData::new(image.into_raw());
-                            let image_key = ImageKey::new(IdNamespace(0), self.image_id);
-                            let mut txn = Transaction::new();
+                        if image.height() > image.width() {
+                            width /= image.height() as f32;
+                            width *= image.width() as f32;
+                        } else {
+                            height /= image.width() as f32;
+                            height *= image.height() as f32;
+                        }
 
-                            self.image_id += 1;
+                        let image =
+                            resize(&image, width as u32, height as u32, FilterType::Lanczos3);
+                        let image_key =
+                            wrapper.load_image(image.into_raw(), width as i32, height as i32);
 
-                            txn.add_image(image_key, image_descriptor, image_data, None);
-                            wrapper
-                                .api_mutex
-                                .lock_poisoned()
-                                .send_transaction(wrapper.document_id, txn);
+                        Some(Rc::new(DeviceIcon::new(image_key, width, height)))
+                    }
+                    Err(_) => None,
+                };
 
-                            Some(Rc::new(DeviceIcon::new(image_key, width, height)))
-                        }
-                        Err(_) => None,
-                    },
+                self.device_icon_option_hashmap.insert(
+                    *socket_addr,
+                    (driver.connection_instant, device_icon_option),
                 );
             }
 
             for serial_number in driver.device_list.serial_number_vec.iter() {
+                let connection_instant = driver.device_connection_instant_hashmap[serial_number];
+
+                // filtered out devices fade out like disconnected ones
+                if !matches_search(
+                    &driver.driver_configuration_descriptor.device_name,
+                    serial_number,
+                    &self.search_text_input.text,
+                ) {
+                    continue;
+                }
+
                 if let Some((index, _)) =
                     self.device_data_vec
                         .iter()
@@ -292,6 +668,21 @@ impl DocumentTrait for DeviceList {
                         })
                 {
                     device_data_to_keep_hashset.insert(index);
+                    self.device_data_vec[index].is_duplicate =
+                        serial_number_count_hashmap[serial_number] > 1;
+                    self.device_data_vec[index].icon_option =
+                        self.device_icon_option_hashmap[socket_addr].1.clone();
+                    self.device_data_vec[index].connection_instant = connection_instant;
+
+                    // matching the search again while fading out
+                    if self.device_data_vec[index].to_remove {
+                        self.device_data_vec[index].to_remove = false;
+                        self.device_data_vec[index].animation.to(
+                            1.0,
+                            Duration::from_millis(400),
+                            AnimationCurve::EASE_IN_OUT,
+                        );
+                    }
                 } else {
                     // create a new device data
                     let mut animation =
@@ -314,7 +705,8 @@ impl DocumentTrait for DeviceList {
                     self.device_data_vec.push(DeviceData::new(
                         DeviceId::new(*socket_addr, serial_number.clone()),
                         driver.driver_configuration_descriptor.device_name.clone(),
-                        self.device_icon_option_hashmap[socket_addr].clone(),
+                        self.device_icon_option_hashmap[socket_addr].1.clone(),
+                        connection_instant,
                         animation,
                         Animation::new(
                             ColorF::new_u(33, 33, 33, 0),
@@ -325,15 +717,10 @@ impl DocumentTrait for DeviceList {
                         property_key,
                         over_color_key,
                     ));
-                }
 
-                // calculate the next button position
-                // 310 = current button width + spacing + next button width
-                if device_button_layout_point.x < frame_size.width - 310.0 {
-                    device_button_layout_point.x += 160.0;
-                } else {
-                    device_button_layout_point.x = 0.0;
-                    device_button_layout_point.y += 160.0;
+                    if let Some(device_data) = self.device_data_vec.last_mut() {
+                        device_data.is_duplicate = serial_number_count_hashmap[serial_number] > 1;
+                    }
                 }
             }
         }
@@ -349,8 +736,14 @@ impl DocumentTrait for DeviceList {
             }
         }
 
-        // 150 = current button row height
-        frame_size.height = device_button_layout_point.y + 150.0;
+        self.sort_device_data();
+
+        // the removed buttons are still drawn until their animation ends
+        let column_count = column_count(frame_size.width);
+        let row_count = (self.device_data_vec.len() + column_count - 1) / column_count;
+
+        frame_size.height = row_count.max(1) as f32 * (DEVICE_BUTTON_SIZE + DEVICE_BUTTON_SPACING)
+            + SEARCH_INPUT_HEIGHT;
         frame_size
     }
 
@@ -362,15 +755,126 @@ impl DocumentTrait for DeviceList {
         wrapper: &mut WindowWrapper<GlobalState>,
     ) {
         let builder = &mut frame_builder.builder;
-        let mut device_button_layout_point = LayoutPoint::zero();
+        let column_count = column_count(frame_size.width);
         let mut device_id_vec = wrapper.global_state.device_id_vec_mutex.lock_poisoned();
 
         device_id_vec.clear();
 
+        // search input
+        let search_input_layout_rect = LayoutRect::from_origin_and_size(
+            LayoutPoint::zero(),
+            LayoutSize::new(
+                frame_size.width - SORT_MODE_BUTTON_WIDTH - DEVICE_BUTTON_SPACING,
+                SEARCH_INPUT_HEIGHT,
+            ),
+        );
+
+        builder.push_rounded_rect(
+            &CommonItemProperties::new(search_input_layout_rect, space_and_clip),
+            ColorF::new_u(66, 66, 66, 100),
+            BorderRadius::uniform(3.0),
+            ClipMode::Clip,
+        );
+        builder.push_hit_test(
+            search_input_layout_rect,
+            space_and_clip.clip_chain_id,
+            space_and_clip.spatial_id,
+            PrimitiveFlags::empty(),
+            AppEvent::SearchInput.tag(HitTag::None),
+        );
+
+        // sort mode button
+        let sort_mode_button_layout_rect = LayoutRect::from_origin_and_size(
+            LayoutPoint::new(frame_size.width - SORT_MODE_BUTTON_WIDTH, 0.0),
+            LayoutSize::new(SORT_MODE_BUTTON_WIDTH, SEARCH_INPUT_HEIGHT),
+        );
+        let sort_mode_button_common_item_properties =
+            &CommonItemProperties::new(sort_mode_button_layout_rect, space_and_clip);
+
+        builder.push_rounded_rect(
+            &sort_mode_button_common_item_properties,
+            ColorF::new_u(66, 66, 66, 100),
+            BorderRadius::uniform(3.0),
+            ClipMode::Clip,
+        );
+        builder.push_rounded_rect_with_animation(
+            &sort_mode_button_common_item_properties,
+            PropertyBinding::Binding(
+                self.sort_mode_button_color_key,
+                self.sort_mode_button_color_animation.value,
+            ),
+            BorderRadius::uniform(3.0),
+            ClipMode::Clip,
+        );
+        self.sort_mode_text.push_text_aligned(
+            builder,
+            space_and_clip,
+            sort_mode_button_layout_rect.min + LayoutSize::new(0.0, 4.0),
+            SORT_MODE_BUTTON_WIDTH,
+            TextAlignment::Center,
+            ColorF::WHITE,
+            None,
+        );
+        builder.push_hit_test(
+            sort_mode_button_layout_rect,
+            space_and_clip.clip_chain_id,
+            space_and_clip.spatial_id,
+            PrimitiveFlags::empty(),
+            AppEvent::SortModeButton.tag(HitTag::None),
+        );
+
+        if self.search_text_input.text.is_empty() && !self.search_text_input.focused {
+            wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_13px"]
+                .create_text("Search by name or serial number".to_string(), None)
+                .push_text(
+                    builder,
+                    space_and_clip,
+                    LayoutPoint::new(10.0, 4.0),
+                    ColorF::new_u(120, 120, 120, 255),
+                    None,
+                );
+        } else {
+            self.search_text_input.push_text(
+                builder,
+                space_and_clip,
+                LayoutPoint::new(10.0, 4.0),
+                ColorF::WHITE,
+                None,
+            );
+        }
+
+        if self.device_data_vec.is_empty() && self.search_text_input.text.is_empty() {
+            builder.push_simple_stacking_context_with_filters(
+                LayoutPoint::zero(),
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                &[FilterOp::Opacity(
+                    PropertyBinding::Binding(
+                        self.searching_opacity_key,
+                        self.searching_opacity_animation.value,
+                    ),
+                    self.searching_opacity_animation.value,
+                )],
+                &[],
+                &[],
+            );
+            self.searching_text.push_text_aligned(
+                builder,
+                space_and_clip,
+                LayoutPoint::new(0.0, SEARCH_INPUT_HEIGHT + DEVICE_BUTTON_SPACING),
+                frame_size.width,
+                TextAlignment::Center,
+                ColorF::new_u(120, 120, 120, 255),
+                None,
+            );
+            builder.pop_stacking_context();
+        }
+
         for (index, device_data) in self.device_data_vec.iter().enumerate() {
+            let device_button_layout_point = device_button_position(index, column_count);
             let device_button_layout_rect = LayoutRect::from_origin_and_size(
                 device_button_layout_point,
-                LayoutSize::new(150.0, 150.0),
+                LayoutSize::splat(DEVICE_BUTTON_SIZE),
             );
             let device_button_common_item_properties =
                 &CommonItemProperties::new(device_button_layout_rect, space_and_clip);
@@ -408,7 +912,7 @@ impl DocumentTrait for DeviceList {
                 space_and_clip.clip_chain_id,
                 space_and_clip.spatial_id,
                 PrimitiveFlags::empty(),
-                (AppEvent::ChooseDeviceButton.into(), index as u16),
+                AppEvent::ChooseDeviceButton.tag(HitTag::Index(index as u16)),
             );
             device_id_vec.push(device_data.device_id.clone());
 
@@ -436,60 +940,137 @@ impl DocumentTrait for DeviceList {
             let font_hashmap = wrapper.global_state.font_hashmap_mutex.lock_poisoned();
 
             font_hashmap["OpenSans_13px"]
-                .create_text(
-                    device_data
-                        .device_name
-                        .get(0..device_data.device_name.len().min(16))
-                        .unwrap_or_default()
-                        .to_string(),
-                    None,
+                .create_wrapped_text(
+                    device_data.device_name.clone(),
+                    DEVICE_BUTTON_SIZE - 15.0,
+                    Some(2),
                 )
-                .push_text(
+                .push_text_aligned(
                     builder,
                     space_and_clip,
                     device_button_layout_point + LayoutSize::new(7.5, 7.5),
+                    DEVICE_BUTTON_SIZE - 15.0,
+                    TextAlignment::Center,
                     ColorF::WHITE,
                     None,
                 );
-            font_hashmap["OpenSans_10px"]
-                .create_text(
-                    device_data
-                        .device_id
-                        .serial_number
-                        .get(0..device_data.device_id.serial_number.len().min(21))
-                        .unwrap_or_default()
-                        .to_string(),
+
+            // clicking the serial number copies it
+            let serial_number_text = font_hashmap["OpenSans_10px"].create_text_ellipsized(
+                match &self.copied_device_id_option {
+                    Some((copied_device_id, _)) if *copied_device_id == device_data.device_id => {
+                        "Serial number copied".to_string()
+                    }
+                    _ => device_data.device_id.serial_number.clone(),
+                },
+                DEVICE_BUTTON_SIZE - 15.0,
+            );
+            let serial_number_layout_rect = LayoutRect::from_origin_and_size(
+                device_button_layout_point + LayoutSize::new(7.5, 130.0),
+                serial_number_text.size,
+            );
+
+            serial_number_text.push_text(
+                builder,
+                space_and_clip,
+                serial_number_layout_rect.min,
+                ColorF::WHITE,
+                None,
+            );
+            builder.push_hit_test(
+                serial_number_layout_rect,
+                space_and_clip.clip_chain_id,
+                space_and_clip.spatial_id,
+                PrimitiveFlags::empty(),
+                AppEvent::CopySerialNumber.tag(HitTag::Index(index as u16)),
+            );
+
+            // the same serial number is reported by another driver,
+            // show the driver port to tell the devices apart
+            if device_data.is_duplicate {
+                let duplicate_text = font_hashmap["OpenSans_10px"].create_text(
+                    format!(
+                        "Duplicate serial | port {}",
+                        device_data.device_id.socket_addr.port()
+                    ),
                     None,
-                )
-                .push_text(
+                );
+                let duplicate_badge_layout_rect = LayoutRect::from_origin_and_size(
+                    device_button_layout_point + LayoutSize::new(5.0, 110.0),
+                    LayoutSize::new(duplicate_text.size.width + 10.0, 17.0),
+                );
+
+                builder.push_rounded_rect(
+                    &CommonItemProperties::new(duplicate_badge_layout_rect, space_and_clip),
+                    ColorF::new_u(230, 126, 34, 200),
+                    BorderRadius::uniform(3.0),
+                    ClipMode::Clip,
+                );
+                duplicate_text.push_text(
                     builder,
                     space_and_clip,
-                    device_button_layout_point + LayoutSize::new(7.5, 130.0),
+                    duplicate_badge_layout_rect.min + LayoutSize::new(5.0, 2.0),
                     ColorF::WHITE,
                     None,
                 );
-            builder.pop_stacking_context();
-
-            // calculate the next button position
-            // 310 = current button width + spacing + next button width
-            if device_button_layout_point.x < frame_size.width - 310.0 {
-                device_button_layout_point.x += 160.0;
-            } else {
-                device_button_layout_point.x = 0.0;
-                device_button_layout_point.y += 160.0;
             }
+            builder.pop_stacking_context();
         }
     }
 
-    fn unload(&mut self, api_mutex: Arc<Mutex<RenderApi>>, document_id: DocumentId) {
-        for device_icon_option in self.device_icon_option_hashmap.values() {
+    fn unload(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        for (_, device_icon_option) in self.device_icon_option_hashmap.values() {
             // unload image
             if let Some(device_icon) = device_icon_option {
-                let mut txn = Transaction::new();
-
-                txn.delete_image(device_icon.image_key);
-                api_mutex.lock_poisoned().send_transaction(document_id, txn);
+                wrapper.unload_image(device_icon.image_key);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn column_count_fits_whole_buttons() {
+        assert_eq!(column_count(0.0), 1);
+        assert_eq!(column_count(DEVICE_BUTTON_SIZE), 1);
+        assert_eq!(
+            column_count(DEVICE_BUTTON_SIZE * 2.0 + DEVICE_BUTTON_SPACING - 1.0),
+            1
+        );
+        assert_eq!(
+            column_count(DEVICE_BUTTON_SIZE * 2.0 + DEVICE_BUTTON_SPACING),
+            2
+        );
+    }
+
+    #[test]
+    fn device_button_position_fills_rows_under_the_search_input() {
+        let step = DEVICE_BUTTON_SIZE + DEVICE_BUTTON_SPACING;
+        let top = SEARCH_INPUT_HEIGHT + DEVICE_BUTTON_SPACING;
+
+        assert_eq!(device_button_position(0, 3), LayoutPoint::new(0.0, top));
+        assert_eq!(
+            device_button_position(2, 3),
+            LayoutPoint::new(step * 2.0, top)
+        );
+        assert_eq!(
+            device_button_position(3, 3),
+            LayoutPoint::new(0.0, top + step)
+        );
+        assert_eq!(
+            device_button_position(4, 1),
+            LayoutPoint::new(0.0, top + step * 4.0)
+        );
+    }
+
+    #[test]
+    fn matches_search_ignores_case() {
+        assert!(matches_search("MMO7 Mouse", "ABC123", "mouse"));
+        assert!(matches_search("MMO7 Mouse", "ABC123", "abc"));
+        assert!(matches_search("MMO7 Mouse", "ABC123", ""));
+        assert!(!matches_search("MMO7 Mouse", "ABC123", "keyboard"));
+    }
+}
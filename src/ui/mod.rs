@@ -1,16 +1,19 @@
 mod app;
 mod device_configurator;
 mod device_list;
+mod text_input;
 
-use std::sync::{Arc, Mutex, MutexGuard};
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::MutexGuard;
 use std::time::Duration;
 
-use crate::animation::Animation;
+use crate::animation::{Animation, AnimationCurve};
 use crate::window::ext::ColorFTrait;
 use crate::window::{
     Event, FrameBuilder, GlobalStateTrait, Text, WindowInitTrait, WindowTrait, WindowWrapper,
 };
-use crate::{DeviceId, GlobalState};
+use crate::{CloseBehavior, DeviceId, GlobalState};
 
 use hashbrown::{HashMap, HashSet};
 use num::FromPrimitive;
@@ -20,11 +23,11 @@ use util::thread::MutexTrait;
 use util::time::Timer;
 use webrender::api::units::{Au, LayoutPoint, LayoutRect, LayoutSize, LayoutVector2D};
 use webrender::api::{
-    APZScrollGeneration, ColorF, CommonItemProperties, DocumentId, ExternalScrollId,
-    HasScrollLinkedEffect, HitTestResultItem, PipelineId, PrimitiveFlags, PropertyBindingKey,
-    RenderReasons, SampledScrollOffset, SpaceAndClipInfo, SpatialTreeItemKey,
+    APZScrollGeneration, ColorF, CommonItemProperties, DynamicProperties, ExternalScrollId,
+    FilterOp, HitTestResultItem, PipelineId, PrimitiveFlags, PropertyBinding, PropertyBindingKey,
+    PropertyValue, RenderReasons, SampledScrollOffset, SpaceAndClipInfo,
 };
-use webrender::{RenderApi, Transaction};
+use webrender::Transaction;
 use winit::dpi::PhysicalPosition;
 use winit::event::{ElementState, ModifiersState, MouseButton, VirtualKeyCode};
 
@@ -32,6 +35,12 @@ use self::device_list::DeviceList;
 
 const EXT_SCROLL_ID_ROOT: u64 = 0;
 
+fn transition_animation() -> Animation<f32> {
+    Animation::new(0.0, |from: &f32, to: &f32, value: &mut f32, coef: f64| {
+        *value = (to - from) * coef as f32 + from
+    })
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, FromPrimitive, Debug)]
 pub enum AppEvent {
     Scroll,
@@ -49,19 +58,68 @@ pub enum AppEvent {
     ReturnButton,
     TitleBar,
     ChooseDeviceButton,
+    CopySerialNumber,
     ModeSelectorPrevious,
     ModeSelectorNext,
     ApplyConfig,
+    ResetConfig,
+    CopyMode,
+    PasteMode,
+    ExportProfile,
+    ImportProfile,
+    ApplyToAllCheckbox,
     Parameter,
+    ResetParameter,
+    CaptureParameter,
+    SearchInput,
+    SortModeButton,
 }
 
 impl AppEvent {
-    fn into(self) -> u64 {
-        self as u64
+    fn tag(self, hit_tag: HitTag) -> (u64, u16) {
+        (self as u64, hit_tag.encode())
     }
 
-    fn from(value: u64) -> Option<Self> {
-        FromPrimitive::from_u64(value)
+    fn from_tag(tag: (u64, u16)) -> Option<(Self, HitTag)> {
+        Some((FromPrimitive::from_u64(tag.0)?, HitTag::decode(tag.1)?))
+    }
+}
+
+// secondary part of a hit test tag,
+// the kind is stored in the top 4 bits and the index in the 12 others
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HitTag {
+    None,
+    Index(u16),
+    ScrollId(u16),
+}
+
+impl HitTag {
+    const INDEX_BITS: u16 = 12;
+    const INDEX_MASK: u16 = (1 << Self::INDEX_BITS) - 1;
+
+    // an index too large to fit is refused, its tag decodes to nothing instead of aliasing
+    // another index, so the item is never hit
+    fn encode(self) -> u16 {
+        match self {
+            HitTag::None => 0,
+            HitTag::Index(index) if index <= Self::INDEX_MASK => (1 << Self::INDEX_BITS) | index,
+            HitTag::ScrollId(scroll_id) if scroll_id <= Self::INDEX_MASK => {
+                (2 << Self::INDEX_BITS) | scroll_id
+            }
+            HitTag::Index(_) | HitTag::ScrollId(_) => u16::MAX,
+        }
+    }
+
+    fn decode(value: u16) -> Option<Self> {
+        let index = value & Self::INDEX_MASK;
+
+        match value >> Self::INDEX_BITS {
+            0 if index == 0 => Some(HitTag::None),
+            1 => Some(HitTag::Index(index)),
+            2 => Some(HitTag::ScrollId(index)),
+            _ => None,
+        }
     }
 }
 
@@ -83,7 +141,7 @@ pub enum AppEventType {
 
 pub struct App {
     do_exit: bool,
-    over_states: HashSet<(AppEvent, u16)>,
+    over_states: HashSet<(AppEvent, HitTag)>,
     title_text: Text,
     close_button_color_key: PropertyBindingKey<ColorF>,
     maximize_button_color_key: PropertyBindingKey<ColorF>,
@@ -97,24 +155,111 @@ pub struct App {
     scroll_frame_size: LayoutSize,
     scroll_content_size: LayoutSize,
     resizing: Option<AppEvent>,
+    keyboard_resizing: bool,
     document: Box<dyn DocumentTrait>,
+    // the outgoing document is kept until the transition ends
+    previous_document_option: Option<Box<dyn DocumentTrait>>,
+    transition_animation: Animation<f32>,
+    document_opacity_key: PropertyBindingKey<f32>,
+    previous_document_opacity_key: PropertyBindingKey<f32>,
     update_app_state_timer: Timer,
+    frame_stats_timer: Timer,
 }
 
 impl App {
     fn switch_document(
         &mut self,
         new_document: Box<dyn DocumentTrait>,
-        api: Arc<Mutex<RenderApi>>,
-        document_id: DocumentId,
-        global_state: Arc<GlobalState>,
+        wrapper: &mut WindowWrapper<GlobalState>,
     ) {
-        self.document.unload(api, document_id);
-        self.document = new_document;
-        self.title_text = global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_15px"]
+        let mut previous_document = std::mem::replace(&mut self.document, new_document);
+
+        // an unfinished transition is cut short
+        if let Some(mut previous_document) = self.previous_document_option.take() {
+            previous_document.unload(wrapper);
+        }
+
+        if wrapper
+            .global_state
+            .settings_manager_mutex
+            .lock_poisoned()
+            .config
+            .document_transition
+        {
+            self.previous_document_option = Some(previous_document);
+            self.transition_animation = transition_animation();
+            self.transition_animation.to(
+                1.0,
+                Duration::from_millis(200),
+                AnimationCurve::EASE_IN_OUT,
+            );
+        } else {
+            previous_document.unload(wrapper);
+        }
+
+        self.title_text = wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_15px"]
             .create_text(self.document.get_title().to_string(), None);
 
-        global_state.request_redraw();
+        wrapper.global_state.request_redraw();
+    }
+
+    fn go_back(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if self.document.can_leave(wrapper) {
+            // going back on purpose, nothing to reopen on the next launch
+            wrapper.global_state.set_last_serial_number(None);
+            self.switch_document(Box::new(DeviceList::new(wrapper)), wrapper);
+
+            let mut selected_device_id_option = wrapper
+                .global_state
+                .selected_device_id_option_mutex
+                .lock_poisoned();
+            let mut selected_device_config_option = wrapper
+                .global_state
+                .selected_device_config_option_mutex
+                .lock_poisoned();
+
+            *selected_device_id_option = None;
+            *selected_device_config_option = None;
+        }
+    }
+
+    // unsaved changes are guarded the same way as when going back
+    fn quit(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if self.document.can_leave(wrapper) {
+            self.do_exit = true;
+        } else {
+            // the document tells why, the window may be closed from the taskbar while minimized
+            wrapper.context.window().set_minimized(false);
+            wrapper.context.window().focus_window();
+        }
+    }
+
+    // the close button, alt + f4 or the taskbar, all follow the close behavior
+    fn close(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        let close_behavior = wrapper
+            .global_state
+            .settings_manager_mutex
+            .lock_poisoned()
+            .config
+            .close_behavior;
+
+        match close_behavior {
+            CloseBehavior::Quit => self.quit(wrapper),
+            // the driver connection keeps running while minimized
+            CloseBehavior::Minimize => wrapper.context.window().set_minimized(true),
+        }
+    }
+
+    // the mouse position in the document space, used to select text by dragging
+    fn document_mouse_moved(&mut self, wrapper: &mut WindowWrapper<GlobalState>) {
+        if let Some(mouse_position) = wrapper.mouse_position {
+            let mouse_position = LayoutPoint::new(
+                mouse_position.x as f32 - 10.0,
+                mouse_position.y as f32 - 55.0,
+            ) + self.scroll_offset;
+
+            self.document.mouse_moved(mouse_position, wrapper);
+        }
     }
 
     fn calculate_event(
@@ -123,11 +268,51 @@ impl App {
         wrapper: &mut WindowWrapper<GlobalState>,
         target_event_type: AppEventType,
     ) {
+        // keys are not sent to the document while moving or resizing the window
+        match target_event_type {
+            AppEventType::KeyPressed { keycode, modifiers } => {
+                if self.update_keyboard_resize(keycode, modifiers, wrapper) {
+                    return;
+                }
+            }
+            AppEventType::KeyReleased { .. } | AppEventType::Char(_) => {
+                if self.keyboard_resizing {
+                    return;
+                }
+            }
+            _ => {}
+        }
+
+        // shortcuts are left to the document while it is typing
+        if let AppEventType::KeyPressed { keycode, modifiers } = target_event_type {
+            if !self.document.is_capturing_keys() {
+                let device_selected = wrapper
+                    .global_state
+                    .selected_device_id_option_mutex
+                    .lock_poisoned()
+                    .is_some();
+
+                match keycode {
+                    VirtualKeyCode::Escape if device_selected => {
+                        self.go_back(wrapper);
+
+                        return;
+                    }
+                    VirtualKeyCode::Q if modifiers.ctrl() => {
+                        self.quit(wrapper);
+
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
         self.document
             .calculate_event(hit_items, wrapper, target_event_type);
 
         if !hit_items.is_empty() {
-            if let Some(event) = AppEvent::from(hit_items[0].tag.0) {
+            if let Some((event, _)) = AppEvent::from_tag(hit_items[0].tag) {
                 match target_event_type {
                     AppEventType::MousePressed => match event {
                         AppEvent::TitleBar => wrapper.context.window().drag_window().unwrap(),
@@ -142,32 +327,13 @@ impl App {
                         _ => {}
                     },
                     AppEventType::MouseReleased => match event {
-                        AppEvent::CloseButton => self.do_exit = true,
+                        AppEvent::CloseButton => self.close(wrapper),
                         AppEvent::MaximizeButton => wrapper
                             .context
                             .window()
                             .set_maximized(!wrapper.context.window().is_maximized()),
                         AppEvent::MinimizeButton => wrapper.context.window().set_minimized(true),
-                        AppEvent::ReturnButton => {
-                            self.switch_document(
-                                Box::new(DeviceList::new()),
-                                wrapper.api_mutex.clone(),
-                                wrapper.document_id,
-                                wrapper.global_state.clone(),
-                            );
-
-                            let mut selected_device_id_option = wrapper
-                                .global_state
-                                .selected_device_id_option_mutex
-                                .lock_poisoned();
-                            let mut selected_device_config_option = wrapper
-                                .global_state
-                                .selected_device_config_option_mutex
-                                .lock_poisoned();
-
-                            *selected_device_id_option = None;
-                            *selected_device_config_option = None;
-                        }
+                        AppEvent::ReturnButton => self.go_back(wrapper),
                         _ => {}
                     },
                     _ => {}
@@ -184,8 +350,8 @@ impl App {
         let mut new_over_state = HashSet::new();
 
         for hit_item in hit_items {
-            if let Some(event) = AppEvent::from(hit_item.tag.0) {
-                new_over_state.insert((event, hit_item.tag.1));
+            if let Some(event) = AppEvent::from_tag(hit_item.tag) {
+                new_over_state.insert(event);
             }
         }
 
@@ -198,6 +364,23 @@ impl App {
         self.over_states = new_over_state;
     }
 
+    // hits are ordered front to back, during the cross-fade the ones behind
+    // the root scroll hit test belong to the previous document
+    fn drop_previous_document_hits(&self, hit_items: &mut Vec<HitTestResultItem>) {
+        if self.previous_document_option.is_none() {
+            return;
+        }
+
+        let root_scroll_tag = AppEvent::Scroll.tag(HitTag::ScrollId(EXT_SCROLL_ID_ROOT as u16));
+
+        if let Some(index) = hit_items
+            .iter()
+            .position(|hit_item| hit_item.tag == root_scroll_tag)
+        {
+            hit_items.truncate(index + 1);
+        }
+    }
+
     fn calculate_wheel_scroll(
         &mut self,
         delta: PhysicalPosition<f64>,
@@ -205,8 +388,10 @@ impl App {
         wrapper: &mut WindowWrapper<GlobalState>,
     ) {
         for hit_item in hit_items {
-            if let Some(AppEvent::Scroll) = AppEvent::from(hit_item.tag.0) {
-                if hit_item.tag.1 == EXT_SCROLL_ID_ROOT as u16 {
+            if let Some((AppEvent::Scroll, HitTag::ScrollId(scroll_id))) =
+                AppEvent::from_tag(hit_item.tag)
+            {
+                if scroll_id == EXT_SCROLL_ID_ROOT as u16 {
                     self.scroll_offset = LayoutVector2D::new(
                         (self.scroll_offset.x - delta.x as f32).max(0.0).min(
                             (self.scroll_content_size.width - self.scroll_frame_size.width)
@@ -243,25 +428,18 @@ impl App {
         self.document.update_app_state(wrapper);
 
         // switch back to device list when the device disconnect
-        let driver_hashmap = wrapper.global_state.driver_hashmap_mutex.lock_poisoned();
-        let selected_device_id_option = wrapper
-            .global_state
-            .selected_device_id_option_mutex
-            .lock_poisoned();
-        let selected_device_config_option = wrapper
-            .global_state
+        let global_state = wrapper.global_state.clone();
+        let driver_hashmap = global_state.driver_hashmap_mutex.lock_poisoned();
+        let selected_device_id_option =
+            global_state.selected_device_id_option_mutex.lock_poisoned();
+        let selected_device_config_option = global_state
             .selected_device_config_option_mutex
             .lock_poisoned();
 
         let mut switch_to_device_list =
             |mut selected_device_id_option: MutexGuard<Option<DeviceId>>,
              mut selected_device_config_option: MutexGuard<Option<DeviceConfig>>| {
-                self.switch_document(
-                    Box::new(DeviceList::new()),
-                    wrapper.api_mutex.clone(),
-                    wrapper.document_id,
-                    wrapper.global_state.clone(),
-                );
+                self.switch_document(Box::new(DeviceList::new(wrapper)), wrapper);
 
                 *selected_device_id_option = None;
                 *selected_device_config_option = None;
@@ -292,7 +470,6 @@ impl WindowInitTrait<GlobalState> for App {
             value.a = (to.a - from.a) * coef as f32 + from.a
         };
         let window_size = wrapper.get_window_size();
-        let document = Box::new(DeviceList::new());
         let mut font_hashmap = HashMap::new();
 
         font_hashmap.insert(
@@ -308,11 +485,23 @@ impl WindowInitTrait<GlobalState> for App {
             wrapper.load_font("OpenSans", Au::from_f32_px(10.0)),
         );
 
-        let title_text =
-            font_hashmap["OpenSans_15px"].create_text(document.get_title().to_string(), None);
-
         *wrapper.global_state.font_hashmap_mutex.lock_poisoned() = font_hashmap;
 
+        // the fonts have to be shared before the document creates its texts
+        let last_serial_number_option = wrapper
+            .global_state
+            .ui_state_manager_mutex
+            .lock_poisoned()
+            .config
+            .last_serial_number
+            .clone();
+        let document = Box::new(DeviceList::with_auto_open(
+            last_serial_number_option,
+            wrapper,
+        ));
+        let title_text = wrapper.global_state.font_hashmap_mutex.lock_poisoned()["OpenSans_15px"]
+            .create_text(document.get_title().to_string(), None);
+
         let api = wrapper.api_mutex.lock_poisoned();
 
         Box::new(Self {
@@ -346,8 +535,14 @@ impl WindowInitTrait<GlobalState> for App {
             ),
             scroll_content_size: LayoutSize::zero(),
             resizing: None,
+            keyboard_resizing: false,
             document,
+            previous_document_option: None,
+            transition_animation: transition_animation(),
+            document_opacity_key: api.generate_property_binding_key(),
+            previous_document_opacity_key: api.generate_property_binding_key(),
             update_app_state_timer: Timer::new(Duration::from_millis(100)),
+            frame_stats_timer: Timer::new(Duration::from_millis(250)),
         })
     }
 }
@@ -356,9 +551,11 @@ impl WindowTrait<GlobalState> for App {
     fn on_event(
         &mut self,
         event: Event,
-        hit_items: Vec<HitTestResultItem>,
+        mut hit_items: Vec<HitTestResultItem>,
         wrapper: &mut WindowWrapper<GlobalState>,
     ) {
+        self.drop_previous_document_hits(&mut hit_items);
+
         match event {
             Event::Resized => {
                 self.scroll_frame_size = LayoutSize::new(
@@ -375,12 +572,15 @@ impl WindowTrait<GlobalState> for App {
                 self.calculate_event(&hit_items, wrapper, AppEventType::Focus(focused));
             }
             Event::MousePressed(MouseButton::Left) => {
+                // cursor moves are coalesced, the document gets the pressed position first
+                self.document_mouse_moved(wrapper);
                 self.calculate_event(&hit_items, wrapper, AppEventType::MousePressed);
             }
             Event::MouseReleased(MouseButton::Left) => {
                 self.calculate_event(&hit_items, wrapper, AppEventType::MouseReleased);
             }
             Event::MousePosition => {
+                self.document_mouse_moved(wrapper);
                 self.update_over_states(hit_items, wrapper);
             }
             Event::MouseWheel(delta) => {
@@ -415,6 +615,8 @@ impl WindowTrait<GlobalState> for App {
             Event::DeviceMotion(delta) => {
                 self.update_window_resize(delta, wrapper);
             }
+            Event::FileDropped(path) => self.document.file_dropped(path, wrapper),
+            Event::CloseRequested => self.close(wrapper),
             Event::DeviceReleased(button) => {
                 // mouse left button
                 if button == 1 {
@@ -432,25 +634,42 @@ impl WindowTrait<GlobalState> for App {
     }
 
     fn animate(&mut self, txn: &mut Transaction, wrapper: &mut WindowWrapper<GlobalState>) {
-        if let Some(new_document) = wrapper
+        let new_document_option = wrapper
             .global_state
             .new_document_option_mutex
             .lock_poisoned()
-            .take()
-        {
-            self.switch_document(
-                new_document,
-                wrapper.api_mutex.clone(),
-                wrapper.document_id,
-                wrapper.global_state.clone(),
-            );
+            .take();
+
+        if let Some(new_document) = new_document_option {
+            self.switch_document(new_document, wrapper);
         }
 
         if self.update_app_state_timer.check() {
             self.update_app_state(wrapper);
         }
 
+        if self.transition_animation.update() {
+            txn.append_dynamic_properties(DynamicProperties {
+                transforms: vec![],
+                floats: vec![
+                    PropertyValue {
+                        key: self.document_opacity_key,
+                        value: self.transition_animation.value,
+                    },
+                    PropertyValue {
+                        key: self.previous_document_opacity_key,
+                        value: 1.0 - self.transition_animation.value,
+                    },
+                ],
+                colors: vec![],
+            });
+        } else if let Some(mut previous_document) = self.previous_document_option.take() {
+            previous_document.unload(wrapper);
+            wrapper.global_state.request_redraw();
+        }
+
         self.animate_title_bar(txn);
+        self.animate_frame_stats(wrapper);
         self.document.animate(txn, wrapper);
     }
 
@@ -473,7 +692,15 @@ impl WindowTrait<GlobalState> for App {
         frame_builder.builder.push_rect(
             &CommonItemProperties::new(background_size, frame_builder.space_and_clip),
             background_size,
-            ColorF::new_u(33, 33, 33, 240),
+            ColorF::new_u(
+                33,
+                33,
+                33,
+                wrapper
+                    .global_state
+                    .background_alpha
+                    .load(Ordering::Relaxed),
+            ),
         );
 
         // calcultate the scroll frame content size
@@ -488,36 +715,65 @@ impl WindowTrait<GlobalState> for App {
             PrimitiveFlags::empty(),
         );
 
-        frame_builder.builder.push_hit_test(
+        let space_and_clip = frame_builder.push_scroll_region(
+            frame_builder.space_and_clip,
+            EXT_SCROLL_ID_ROOT,
+            self.scroll_content_size,
             LayoutRect::from_size(self.scroll_frame_size),
-            frame_builder.space_and_clip.clip_chain_id,
-            frame_builder.space_and_clip.spatial_id,
-            PrimitiveFlags::empty(),
-            (AppEvent::Scroll.into(), EXT_SCROLL_ID_ROOT as u16),
+            LayoutVector2D::zero(),
         );
 
-        let spatial_id = frame_builder.builder.define_scroll_frame(
-            frame_builder.space_and_clip.spatial_id,
-            ExternalScrollId(EXT_SCROLL_ID_ROOT, PipelineId::dummy()),
-            LayoutRect::from_size(self.scroll_content_size),
+        // draw the scroll frame content, cross-fading with the previous document
+        if let Some(previous_document) = &self.previous_document_option {
+            let opacity = 1.0 - self.transition_animation.value;
+
+            frame_builder
+                .builder
+                .push_simple_stacking_context_with_filters(
+                    LayoutPoint::zero(),
+                    space_and_clip.spatial_id,
+                    PrimitiveFlags::empty(),
+                    &[FilterOp::Opacity(
+                        PropertyBinding::Binding(self.previous_document_opacity_key, opacity),
+                        opacity,
+                    )],
+                    &[],
+                    &[],
+                );
+            previous_document.draw(
+                self.scroll_frame_size,
+                frame_builder,
+                space_and_clip,
+                wrapper,
+            );
+            frame_builder.builder.pop_stacking_context();
+            frame_builder
+                .builder
+                .push_simple_stacking_context_with_filters(
+                    LayoutPoint::zero(),
+                    space_and_clip.spatial_id,
+                    PrimitiveFlags::empty(),
+                    &[FilterOp::Opacity(
+                        PropertyBinding::Binding(
+                            self.document_opacity_key,
+                            self.transition_animation.value,
+                        ),
+                        self.transition_animation.value,
+                    )],
+                    &[],
+                    &[],
+                );
+        }
+
+        // pushed above the previous document, every hit behind it is dropped
+        frame_builder.builder.push_hit_test(
             LayoutRect::from_size(self.scroll_frame_size),
-            LayoutVector2D::zero(),
-            APZScrollGeneration::default(),
-            HasScrollLinkedEffect::No,
-            SpatialTreeItemKey::new(0, 0),
-        );
-        let clip_id = frame_builder.builder.define_clip_rect(
+            frame_builder.space_and_clip.clip_chain_id,
             frame_builder.space_and_clip.spatial_id,
-            LayoutRect::from_size(self.scroll_frame_size),
+            PrimitiveFlags::empty(),
+            AppEvent::Scroll.tag(HitTag::ScrollId(EXT_SCROLL_ID_ROOT as u16)),
         );
-        let space_and_clip = SpaceAndClipInfo {
-            spatial_id,
-            clip_chain_id: frame_builder
-                .builder
-                .define_clip_chain(Some(frame_builder.space_and_clip.clip_chain_id), [clip_id]),
-        };
 
-        // draw the scroll frame content
         self.document.draw(
             self.scroll_frame_size,
             frame_builder,
@@ -525,6 +781,10 @@ impl WindowTrait<GlobalState> for App {
             wrapper,
         );
 
+        if self.previous_document_option.is_some() {
+            frame_builder.builder.pop_stacking_context();
+        }
+
         frame_builder.builder.pop_stacking_context();
 
         // draw main window elements
@@ -534,6 +794,7 @@ impl WindowTrait<GlobalState> for App {
             wrapper.global_state.clone(),
         );
         self.draw_window_resize(wrapper.window_size, frame_builder);
+        self.draw_frame_stats(frame_builder, wrapper);
 
         frame_builder.builder.pop_stacking_context();
     }
@@ -567,8 +828,10 @@ impl WindowTrait<GlobalState> for App {
             font.unload();
         }
 
-        self.document
-            .unload(wrapper.api_mutex.clone(), wrapper.document_id);
+        if let Some(mut previous_document) = self.previous_document_option.take() {
+            previous_document.unload(wrapper);
+        }
+        self.document.unload(wrapper);
     }
 }
 
@@ -583,10 +846,29 @@ pub trait DocumentTrait {
     ) {
     }
 
-    fn update_over_state(&mut self, _new_over_state: &HashSet<(AppEvent, u16)>) {}
+    // called before going back to the previous document, false keeps the document open
+    fn can_leave(&mut self, _wrapper: &mut WindowWrapper<GlobalState>) -> bool {
+        true
+    }
+
+    // true while a text input is focused or a key is being captured, app shortcuts are disabled
+    fn is_capturing_keys(&self) -> bool {
+        false
+    }
+
+    fn update_over_state(&mut self, _new_over_state: &HashSet<(AppEvent, HitTag)>) {}
+
+    fn mouse_moved(
+        &mut self,
+        _mouse_position: LayoutPoint,
+        _wrapper: &mut WindowWrapper<GlobalState>,
+    ) {
+    }
 
     fn update_app_state(&mut self, _wrapper: &mut WindowWrapper<GlobalState>) {}
 
+    fn file_dropped(&mut self, _path: PathBuf, _wrapper: &mut WindowWrapper<GlobalState>) {}
+
     fn animate(&mut self, _txn: &mut Transaction, _wrapper: &mut WindowWrapper<GlobalState>) {}
 
     fn calculate_size(
@@ -603,5 +885,37 @@ pub trait DocumentTrait {
         wrapper: &mut WindowWrapper<GlobalState>,
     );
 
-    fn unload(&mut self, _api_mutex: Arc<Mutex<RenderApi>>, _document_id: DocumentId) {}
+    fn unload(&mut self, _wrapper: &mut WindowWrapper<GlobalState>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hit_tag_round_trip() {
+        for hit_tag in [
+            HitTag::None,
+            HitTag::Index(0),
+            HitTag::Index(42),
+            HitTag::Index(HitTag::INDEX_MASK),
+            HitTag::ScrollId(0),
+            HitTag::ScrollId(HitTag::INDEX_MASK),
+        ] {
+            assert_eq!(HitTag::decode(hit_tag.encode()), Some(hit_tag));
+        }
+    }
+
+    #[test]
+    fn hit_tag_refuses_out_of_range_indices() {
+        assert_eq!(
+            HitTag::decode(HitTag::Index(HitTag::INDEX_MASK + 1).encode()),
+            None
+        );
+        assert_eq!(HitTag::decode(HitTag::ScrollId(u16::MAX).encode()), None);
+        assert_ne!(
+            HitTag::Index(HitTag::INDEX_MASK + 1).encode(),
+            HitTag::Index(0).encode()
+        );
+    }
 }
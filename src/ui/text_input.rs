@@ -0,0 +1,586 @@
+use std::cell::Cell;
+use std::iter::once;
+use std::ops::Range;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::window::ext::ColorFTrait;
+use crate::window::{Font, Text};
+
+use copypasta::{ClipboardContext, ClipboardProvider};
+use unicode_segmentation::UnicodeSegmentation;
+use util::thread::MutexTrait;
+use util::time::Timer;
+use webrender::api::units::{LayoutPoint, LayoutRect, LayoutSize};
+use webrender::api::{
+    ColorF, CommonItemProperties, DisplayListBuilder, GlyphOptions, PropertyBinding,
+    PropertyBindingKey, PropertyValue, SpaceAndClipInfo,
+};
+use webrender::RenderApi;
+use winit::event::{ModifiersState, VirtualKeyCode};
+
+// word navigation stops where the kind of character changes, so tags are jumped edge by edge
+#[derive(PartialEq)]
+enum CharKind {
+    Space,
+    Alphanumeric,
+    OpeningBrace,
+    ClosingBrace,
+    Symbol,
+}
+
+impl From<char> for CharKind {
+    fn from(char: char) -> Self {
+        match char {
+            '{' => Self::OpeningBrace,
+            '}' => Self::ClosingBrace,
+            char if char.is_whitespace() => Self::Space,
+            char if char.is_alphanumeric() || char == '_' => Self::Alphanumeric,
+            _ => Self::Symbol,
+        }
+    }
+}
+
+// skip the spaces, then the run of characters of the same kind
+fn previous_word_boundary(text: &str, cursor_position: usize) -> usize {
+    let mut char_iter = text[..cursor_position]
+        .char_indices()
+        .rev()
+        .skip_while(|(_, char)| char.is_whitespace())
+        .peekable();
+    let char_kind = match char_iter.peek() {
+        Some((_, char)) => CharKind::from(*char),
+        None => return 0,
+    };
+
+    char_iter
+        .take_while(|(_, char)| CharKind::from(*char) == char_kind)
+        .last()
+        .map(|(index, _)| index)
+        .unwrap_or(0)
+}
+
+// skip the run of characters of the same kind, then the spaces
+fn next_word_boundary(text: &str, cursor_position: usize) -> usize {
+    let char_kind = match text[cursor_position..].chars().next() {
+        Some(char) => CharKind::from(char),
+        None => return text.len(),
+    };
+
+    text[cursor_position..]
+        .char_indices()
+        .skip_while(|(_, char)| CharKind::from(*char) == char_kind)
+        .find(|(_, char)| !char.is_whitespace())
+        .map(|(index, _)| cursor_position + index)
+        .unwrap_or(text.len())
+}
+
+// what a key did to the text input, so the owner knows what to update
+#[derive(PartialEq)]
+pub enum TextInputEvent {
+    Ignored,
+    CursorMoved,
+    TextChanged,
+}
+
+pub struct TextInput {
+    pub text: String,
+    pub focused: bool,
+    first_text: Text,
+    second_text: Text,
+    pub width: f32,
+    pub height: f32,
+    cursor_height: f32,
+    cursor_position: usize,
+    // the selection goes from the anchor to the cursor
+    selection_anchor: Option<usize>,
+    selection_width: f32,
+    // true while the mouse drags the selection
+    mouse_selecting: bool,
+    // where the text was last drawn, to find the character under the mouse
+    origin: Cell<LayoutPoint>,
+    cursor_color_key: PropertyBindingKey<ColorF>,
+    cursor_color: ColorF,
+    cursor_color_state: bool,
+    cursor_timer: Timer,
+}
+
+impl TextInput {
+    pub fn new(
+        mut text: String,
+        font: &Font,
+        api_mutex: &Mutex<RenderApi>,
+        cursor_color: ColorF,
+        cursor_height: f32,
+    ) -> Self {
+        text.retain(|c| c != '\n' && c != '\r');
+
+        let first_text = font.create_text(text[..0].to_string(), None);
+        let second_text = font.create_text(text[0..].to_string(), None);
+
+        Self {
+            text,
+            focused: false,
+            first_text,
+            width: second_text.size.width,
+            height: second_text.size.height,
+            second_text,
+            cursor_height,
+            cursor_position: 0,
+            selection_anchor: None,
+            selection_width: 0.0,
+            mouse_selecting: false,
+            origin: Cell::new(LayoutPoint::zero()),
+            cursor_color_key: api_mutex.lock_poisoned().generate_property_binding_key(),
+            cursor_color,
+            cursor_color_state: true,
+            cursor_timer: Timer::new(Duration::from_millis(350)),
+        }
+    }
+
+    // a selection started by the mouse is kept when focusing
+    pub fn set_focus(&mut self, focus: bool) {
+        self.focused = focus;
+
+        if !focus {
+            self.selection_anchor = None;
+            self.mouse_selecting = false;
+        }
+
+        self.width = self.first_text.size.width
+            + self.second_text.size.width
+            + (self.focused as u8 as f32 * 5.0);
+    }
+
+    fn update_text(&mut self, font: &Font) {
+        let (first_text, second_text) = self.text.split_at(self.cursor_position);
+
+        self.first_text = font.create_text(first_text.to_string(), None);
+        self.second_text = font.create_text(second_text.to_string(), None);
+        self.width = self.first_text.size.width
+            + self.second_text.size.width
+            + (self.focused as u8 as f32 * 5.0);
+        self.height = self
+            .first_text
+            .size
+            .height
+            .max(self.second_text.size.height);
+        self.selection_width = match self.selection_range() {
+            Some(selection_range) => {
+                font.create_text(self.text[selection_range].to_string(), None)
+                    .size
+                    .width
+            }
+            None => 0.0,
+        };
+    }
+
+    fn selection_range(&self) -> Option<Range<usize>> {
+        match self.selection_anchor {
+            Some(selection_anchor) if selection_anchor != self.cursor_position => Some(
+                selection_anchor.min(self.cursor_position)
+                    ..selection_anchor.max(self.cursor_position),
+            ),
+            _ => None,
+        }
+    }
+
+    fn selected_text(&self) -> Option<&str> {
+        self.selection_range()
+            .map(|selection_range| &self.text[selection_range])
+    }
+
+    // returns true when there was a selection to remove
+    fn delete_selection(&mut self) -> bool {
+        let selection_range_option = self.selection_range();
+
+        self.selection_anchor = None;
+
+        if let Some(selection_range) = selection_range_option {
+            self.cursor_position = selection_range.start;
+            self.text.replace_range(selection_range, "");
+
+            true
+        } else {
+            false
+        }
+    }
+
+    // caret moves on grapheme boundaries so combining marks stay with their base character
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.cursor_position]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(index, _)| index)
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.cursor_position..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| self.cursor_position + grapheme.len())
+            .unwrap_or(self.text.len())
+    }
+
+    fn add_char(&mut self, font: &Font, char: char) {
+        self.delete_selection();
+        self.text.insert(self.cursor_position, char);
+        self.cursor_position += char.len_utf8();
+        self.update_text(font);
+    }
+
+    fn add_str(&mut self, font: &Font, text: &str) {
+        self.delete_selection();
+        self.text.insert_str(self.cursor_position, text);
+        self.cursor_position += text.len();
+        self.update_text(font);
+    }
+
+    fn delete_char(&mut self, font: &Font) {
+        if !self.delete_selection() && self.text.len() > self.cursor_position {
+            self.text
+                .replace_range(self.cursor_position..self.next_boundary(), "");
+        }
+
+        self.update_text(font);
+    }
+
+    fn back_char(&mut self, font: &Font) {
+        if !self.delete_selection() && self.cursor_position > 0 {
+            let previous_boundary = self.previous_boundary();
+
+            self.text
+                .replace_range(previous_boundary..self.cursor_position, "");
+            self.cursor_position = previous_boundary;
+        }
+
+        self.update_text(font);
+    }
+
+    fn change_cursor_position(&mut self, font: &Font, cursor_position: usize) {
+        self.cursor_position = cursor_position.min(self.text.len());
+
+        while !self.text.is_char_boundary(self.cursor_position) {
+            self.cursor_position += 1;
+        }
+
+        self.update_text(font);
+    }
+
+    // with shift held the selection is extended, otherwise it is cleared
+    fn update_selection_anchor(&mut self, extend_selection: bool) {
+        if !extend_selection {
+            self.selection_anchor = None;
+        } else if self.selection_anchor.is_none() {
+            self.selection_anchor = Some(self.cursor_position);
+        }
+    }
+
+    fn move_cursor(&mut self, font: &Font, cursor_position: usize, extend_selection: bool) {
+        self.update_selection_anchor(extend_selection);
+        self.change_cursor_position(font, cursor_position);
+    }
+
+    fn cursor_left(&mut self, font: &Font, extend_selection: bool) {
+        self.move_cursor(font, self.previous_boundary(), extend_selection);
+    }
+
+    fn cursor_right(&mut self, font: &Font, extend_selection: bool) {
+        self.move_cursor(font, self.next_boundary(), extend_selection);
+    }
+
+    fn cursor_home(&mut self, font: &Font, extend_selection: bool) {
+        self.move_cursor(font, 0, extend_selection);
+    }
+
+    fn cursor_end(&mut self, font: &Font, extend_selection: bool) {
+        self.move_cursor(font, self.text.len(), extend_selection);
+    }
+
+    fn cursor_previous_word(&mut self, font: &Font, extend_selection: bool) {
+        self.move_cursor(
+            font,
+            previous_word_boundary(&self.text, self.cursor_position),
+            extend_selection,
+        );
+    }
+
+    fn cursor_next_word(&mut self, font: &Font, extend_selection: bool) {
+        self.move_cursor(
+            font,
+            next_word_boundary(&self.text, self.cursor_position),
+            extend_selection,
+        );
+    }
+
+    // grapheme boundary closest to the x position, relative to the text origin
+    fn cursor_position_at(&self, font: &Font, x: f32) -> usize {
+        // while focused the text after the cursor is drawn after a gap
+        let x = if self.focused && x > self.first_text.size.width {
+            (x - 5.0).max(self.first_text.size.width)
+        } else {
+            x
+        };
+        let char_index = font.create_text(self.text.clone(), None).char_index_at(x);
+        let byte_index = self
+            .text
+            .char_indices()
+            .nth(char_index)
+            .map(|(index, _)| index)
+            .unwrap_or(self.text.len());
+
+        self.text
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .chain(once(self.text.len()))
+            .find(|index| *index >= byte_index)
+            .unwrap_or(self.text.len())
+    }
+
+    // the cursor goes under the mouse and a selection starts, shift extends the current one
+    pub fn mouse_pressed(
+        &mut self,
+        font: &Font,
+        mouse_position: LayoutPoint,
+        extend_selection: bool,
+    ) {
+        let cursor_position = self.cursor_position_at(font, mouse_position.x - self.origin.get().x);
+
+        if extend_selection {
+            self.update_selection_anchor(true);
+        } else {
+            self.selection_anchor = Some(cursor_position);
+        }
+
+        self.mouse_selecting = true;
+        self.change_cursor_position(font, cursor_position);
+    }
+
+    pub fn mouse_moved(&mut self, font: &Font, mouse_position: LayoutPoint) -> TextInputEvent {
+        if self.mouse_selecting {
+            let cursor_position =
+                self.cursor_position_at(font, mouse_position.x - self.origin.get().x);
+
+            if cursor_position != self.cursor_position {
+                self.change_cursor_position(font, cursor_position);
+
+                return TextInputEvent::CursorMoved;
+            }
+        }
+
+        TextInputEvent::Ignored
+    }
+
+    pub fn mouse_released(&mut self) {
+        self.mouse_selecting = false;
+    }
+
+    pub fn animate(&mut self) -> Option<PropertyValue<ColorF>> {
+        if self.cursor_timer.check() {
+            self.cursor_color_state = !self.cursor_color_state;
+
+            Some(PropertyValue {
+                key: self.cursor_color_key,
+                value: if self.cursor_color_state {
+                    self.cursor_color
+                } else {
+                    ColorF::TRANSPARENT
+                },
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn push_text(
+        &self,
+        builder: &mut DisplayListBuilder,
+        space_and_clip: SpaceAndClipInfo,
+        position: LayoutPoint,
+        color: ColorF,
+        glyph_options: Option<GlyphOptions>,
+    ) {
+        self.origin.set(position);
+
+        // selection highlight, before or after the cursor gap
+        if let (true, Some(selection_anchor)) = (self.focused, self.selection_anchor) {
+            let selection_x = if selection_anchor < self.cursor_position {
+                self.first_text.size.width - self.selection_width
+            } else {
+                self.first_text.size.width + 5.0
+            };
+            let selection_layout_rect = LayoutRect::from_origin_and_size(
+                position + LayoutSize::new(selection_x, 0.0),
+                LayoutSize::new(self.selection_width, self.cursor_height),
+            );
+
+            builder.push_rect(
+                &CommonItemProperties::new(selection_layout_rect, space_and_clip),
+                selection_layout_rect,
+                ColorF::new_u(52, 152, 219, 150),
+            );
+        }
+
+        self.first_text
+            .push_text(builder, space_and_clip, position, color, glyph_options);
+
+        if self.focused {
+            let cursor_layout_rect = LayoutRect::from_origin_and_size(
+                position + LayoutSize::new(self.first_text.size.width + 2.0, 0.0),
+                LayoutSize::new(1.0, self.cursor_height),
+            );
+            let cursor_common_item_properties =
+                &CommonItemProperties::new(cursor_layout_rect, space_and_clip);
+
+            builder.push_rect_with_animation(
+                cursor_common_item_properties,
+                cursor_layout_rect,
+                PropertyBinding::Binding(self.cursor_color_key, self.cursor_color),
+            );
+        }
+
+        self.second_text.push_text(
+            builder,
+            space_and_clip,
+            position
+                + LayoutSize::new(
+                    self.first_text.size.width + (self.focused as u8 as f32 * 5.0),
+                    0.0,
+                ),
+            color,
+            glyph_options,
+        );
+    }
+
+    pub fn key_pressed(
+        &mut self,
+        font: &Font,
+        keycode: VirtualKeyCode,
+        modifiers: ModifiersState,
+        clipboard_context_option: Option<&mut ClipboardContext>,
+    ) -> TextInputEvent {
+        match keycode {
+            VirtualKeyCode::Left => {
+                if modifiers.ctrl() {
+                    self.cursor_previous_word(font, modifiers.shift());
+                } else {
+                    self.cursor_left(font, modifiers.shift());
+                }
+
+                TextInputEvent::CursorMoved
+            }
+            VirtualKeyCode::Right => {
+                if modifiers.ctrl() {
+                    self.cursor_next_word(font, modifiers.shift());
+                } else {
+                    self.cursor_right(font, modifiers.shift());
+                }
+
+                TextInputEvent::CursorMoved
+            }
+            VirtualKeyCode::Home => {
+                self.cursor_home(font, modifiers.shift());
+
+                TextInputEvent::CursorMoved
+            }
+            VirtualKeyCode::End => {
+                self.cursor_end(font, modifiers.shift());
+
+                TextInputEvent::CursorMoved
+            }
+            VirtualKeyCode::Delete => {
+                self.delete_char(font);
+
+                TextInputEvent::TextChanged
+            }
+            VirtualKeyCode::Back => {
+                self.back_char(font);
+
+                TextInputEvent::TextChanged
+            }
+            VirtualKeyCode::C | VirtualKeyCode::X => {
+                if let (true, Some(clipboard_context)) =
+                    (modifiers.ctrl(), clipboard_context_option)
+                {
+                    // copy the selection, or the whole value when nothing is selected,
+                    // cut only acts on a selection
+                    let text_option = match (self.selected_text(), keycode) {
+                        (Some(text), _) => Some(text),
+                        (None, VirtualKeyCode::C) => Some(self.text.as_str()),
+                        (None, _) => None,
+                    };
+
+                    if let Some(text) = text_option {
+                        if clipboard_context.set_contents(text.to_string()).is_ok()
+                            && keycode == VirtualKeyCode::X
+                            && self.delete_selection()
+                        {
+                            self.update_text(font);
+
+                            return TextInputEvent::TextChanged;
+                        }
+                    }
+                }
+
+                TextInputEvent::Ignored
+            }
+            VirtualKeyCode::V => {
+                if let (true, Some(clipboard_context)) =
+                    (modifiers.ctrl(), clipboard_context_option)
+                {
+                    if let Ok(mut text) = clipboard_context.get_contents() {
+                        text.retain(|c| !c.is_control());
+                        self.add_str(font, text.as_str());
+
+                        return TextInputEvent::TextChanged;
+                    }
+                }
+
+                TextInputEvent::Ignored
+            }
+            _ => TextInputEvent::Ignored,
+        }
+    }
+
+    // control characters are sent as chars too, they are handled as keys instead
+    pub fn char_received(&mut self, font: &Font, char: char) -> TextInputEvent {
+        if !char.is_control() {
+            self.add_char(font, char);
+
+            TextInputEvent::TextChanged
+        } else {
+            TextInputEvent::Ignored
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_boundaries_stop_at_tag_edges() {
+        let text = "{+CTRL}c{-CTRL} ab";
+
+        assert_eq!(next_word_boundary(text, 0), 1);
+        assert_eq!(next_word_boundary(text, 1), 2);
+        assert_eq!(next_word_boundary(text, 2), 6);
+        assert_eq!(next_word_boundary(text, 6), 7);
+        assert_eq!(next_word_boundary(text, 14), 16);
+        assert_eq!(next_word_boundary(text, 16), text.len());
+        assert_eq!(previous_word_boundary(text, text.len()), 16);
+        assert_eq!(previous_word_boundary(text, 16), 14);
+        assert_eq!(previous_word_boundary(text, 6), 2);
+        assert_eq!(previous_word_boundary(text, 0), 0);
+    }
+
+    #[test]
+    fn word_boundaries_keep_multibyte_characters_whole() {
+        let text = "héllo wörld";
+
+        assert_eq!(next_word_boundary(text, 0), 7);
+        assert_eq!(previous_word_boundary(text, text.len()), 7);
+        assert_eq!(previous_word_boundary(text, 7), 0);
+    }
+}
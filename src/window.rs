@@ -2,41 +2,49 @@ pub mod ext;
 
 mod font;
 mod frame_builder;
+mod frame_stats;
 mod notifier;
 
+use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::vec;
 
-pub use font::{Font, Text};
+pub use font::{Font, Text, TextAlignment};
 pub use frame_builder::FrameBuilder;
+pub use frame_stats::FrameStats;
 
 use notifier::Notifier;
 
 use gleam::gl;
 use glutin::{Api, ContextBuilder, GlRequest, PossiblyCurrent, WindowedContext};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
 use image::load_from_memory;
 use util::thread::MutexTrait;
 use util::time::Timer;
 use webrender::api::units::{Au, DeviceIntPoint, DeviceIntRect, DeviceIntSize, WorldPoint};
 use webrender::api::{
-    ColorF, DocumentId, Epoch, FontKey, HitTestResultItem, PipelineId, RenderReasons,
+    ColorF, DocumentId, Epoch, FontKey, HitTestResultItem, ImageData, ImageDescriptor,
+    ImageDescriptorFlags, ImageFormat, ImageKey, PipelineId, RenderReasons,
 };
 use webrender::render_api::{RenderApi, Transaction};
 use webrender::{create_webrender_instance, Renderer, WebRenderOptions};
 use winit::dpi::{PhysicalPosition, PhysicalSize};
 use winit::event::{
-    ButtonId, DeviceEvent, ElementState, KeyboardInput, MouseButton, MouseScrollDelta, WindowEvent,
+    ButtonId, DeviceEvent, ElementState, KeyboardInput, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
 };
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::platform::run_return::EventLoopExtRunReturn;
 use winit::window::{Icon, WindowBuilder};
 
-const LINE_HEIGHT: f32 = 21.0;
+const FOCUSED_FRAME_DURATION: Duration = Duration::from_millis(16);
+const UNFOCUSED_FRAME_DURATION: Duration = Duration::from_millis(250);
+// highest sample count drivers offer, larger values would overflow when rounded
+const MAX_MULTISAMPLING: u16 = 16;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum Event {
     Resized,
     MousePosition,
@@ -50,6 +58,9 @@ pub enum Event {
     Char(char),
     DeviceMotion(PhysicalPosition<f64>),
     DeviceReleased(ButtonId),
+    FileDropped(PathBuf),
+    // the system asks to close the window, the window decides whether it does
+    CloseRequested,
 }
 
 pub struct WindowOptions {
@@ -66,6 +77,10 @@ pub struct WindowOptions {
     pub transparent: bool,
     pub decorations: bool,
     pub always_on_top: bool,
+    pub line_height: f32,
+    pub scroll_speed: f32,
+    pub shift_horizontal_scroll: bool,
+    pub multisampling: u16,
 }
 
 impl WindowOptions {
@@ -84,6 +99,10 @@ impl WindowOptions {
             transparent: false,
             decorations: true,
             always_on_top: false,
+            line_height: 21.0,
+            scroll_speed: 1.0,
+            shift_horizontal_scroll: true,
+            multisampling: 4,
         }
     }
 }
@@ -100,8 +119,13 @@ pub struct WindowWrapper<T: GlobalStateTrait> {
     pub api_mutex: Arc<Mutex<RenderApi>>,
     pub global_state: Arc<T>,
     font_key_hashmap: HashMap<&'static str, FontKey>,
+    // the shaper reads the font file itself
+    font_data_hashmap: HashMap<&'static str, &'static [u8]>,
+    image_key_hashset: HashSet<ImageKey>,
     pub window_size: PhysicalSize<u32>,
     pub mouse_position: Option<PhysicalPosition<f64>>,
+    hit_items_option: Option<Vec<HitTestResultItem>>,
+    pub frame_stats: FrameStats,
 }
 
 impl<T: GlobalStateTrait> WindowWrapper<T> {
@@ -136,9 +160,13 @@ impl<T: GlobalStateTrait> WindowWrapper<T> {
             epoch,
             api_mutex: Arc::new(Mutex::new(api)),
             font_key_hashmap,
+            font_data_hashmap: HashMap::new(),
+            image_key_hashset: HashSet::new(),
             global_state,
             window_size,
             mouse_position: None,
+            hit_items_option: None,
+            frame_stats: FrameStats::new(),
         }
     }
 
@@ -190,8 +218,13 @@ impl<T: GlobalStateTrait> WindowWrapper<T> {
         self.context.window().set_outer_position(position)
     }
 
-    fn do_hit_test(&self) -> Vec<HitTestResultItem> {
-        match self.mouse_position {
+    // the result is reused until the mouse moves or the next frame tick
+    fn do_hit_test(&mut self) -> Vec<HitTestResultItem> {
+        if let Some(hit_items) = &self.hit_items_option {
+            return hit_items.clone();
+        }
+
+        let hit_items = match self.mouse_position {
             Some(mouse_position) => {
                 self.api_mutex
                     .lock_poisoned()
@@ -202,7 +235,16 @@ impl<T: GlobalStateTrait> WindowWrapper<T> {
                     .items
             }
             None => vec![],
-        }
+        };
+
+        self.hit_items_option = Some(hit_items.clone());
+
+        hit_items
+    }
+
+    fn set_mouse_position(&mut self, mouse_position: Option<PhysicalPosition<f64>>) {
+        self.mouse_position = mouse_position;
+        self.hit_items_option = None;
     }
 
     fn redraw(&mut self, window: &mut Box<dyn WindowTrait<T>>, force: bool) {
@@ -210,23 +252,28 @@ impl<T: GlobalStateTrait> WindowWrapper<T> {
 
         window.animate(&mut txn, self);
 
-        if self.global_state.should_redraw() || force {
+        let do_redraw = self.global_state.should_redraw() || force;
+
+        if do_redraw {
+            let build_instant = Instant::now();
             let mut frame_builder = FrameBuilder::new::<T>(self);
 
             window.redraw(&mut frame_builder, self);
             window.set_scroll_offsets(&mut txn);
-            txn.set_display_list(
-                self.epoch,
-                None,
-                frame_builder.layout_size,
-                frame_builder.builder.end(),
-            );
+
+            let display_list = frame_builder.builder.end();
+
+            if self.frame_stats.is_enabled() {
+                self.frame_stats.push_build_time(build_instant.elapsed());
+            }
+
+            txn.set_display_list(self.epoch, None, frame_builder.layout_size, display_list);
         }
 
         if !txn.is_empty() {
             txn.generate_frame(
                 0,
-                if self.global_state.should_redraw() || force {
+                if do_redraw {
                     RenderReasons::SCENE
                 } else {
                     RenderReasons::ANIMATED_PROPERTY
@@ -247,6 +294,7 @@ impl<T: GlobalStateTrait> WindowWrapper<T> {
         txn.add_raw_font(font_key, data.to_vec(), 0);
 
         self.font_key_hashmap.insert(name, font_key);
+        self.font_data_hashmap.insert(name, data);
 
         api.send_transaction(self.document_id, txn);
     }
@@ -254,12 +302,63 @@ impl<T: GlobalStateTrait> WindowWrapper<T> {
     pub fn load_font(&mut self, name: &'static str, font_size: Au) -> Font {
         Font::new(
             self.font_key_hashmap[&name].clone(),
+            self.font_data_hashmap[&name],
             font_size,
             self.api_mutex.clone(),
             self.document_id,
         )
     }
 
+    // keys come from the api so they never collide between documents
+    pub fn load_image(&mut self, rgba: Vec<u8>, width: i32, height: i32) -> ImageKey {
+        let mut api = self.api_mutex.lock_poisoned();
+        let mut txn = Transaction::new();
+
+        let image_key = api.generate_image_key();
+        txn.add_image(
+            image_key,
+            ImageDescriptor::new(
+                width,
+                height,
+                ImageFormat::RGBA8,
+                ImageDescriptorFlags::empty(),
+            ),
+            ImageData::new(rgba),
+            None,
+        );
+
+        self.image_key_hashset.insert(image_key);
+
+        api.send_transaction(self.document_id, txn);
+
+        image_key
+    }
+
+    pub fn unload_image(&mut self, image_key: ImageKey) {
+        if self.image_key_hashset.remove(&image_key) {
+            let mut txn = Transaction::new();
+
+            txn.delete_image(image_key);
+
+            self.api_mutex
+                .lock_poisoned()
+                .send_transaction(self.document_id, txn);
+        }
+    }
+
+    // free the images documents didn't unload themselves
+    fn unload_images(&mut self) {
+        let mut txn = Transaction::new();
+
+        for image_key in self.image_key_hashset.drain() {
+            txn.delete_image(image_key);
+        }
+
+        self.api_mutex
+            .lock_poisoned()
+            .send_transaction(self.document_id, txn);
+    }
+
     fn unload_fonts(&mut self) {
         let mut txn = Transaction::new();
 
@@ -277,10 +376,13 @@ pub struct Window<T: GlobalStateTrait> {
     event_loop: EventLoop<()>,
     pub wrapper: WindowWrapper<T>,
     window: Box<dyn WindowTrait<T>>,
+    line_height: f32,
+    scroll_speed: f32,
+    shift_horizontal_scroll: bool,
 }
 
 impl<T: GlobalStateTrait> Window<T> {
-    pub fn new(window_options: WindowOptions, global_state: Arc<T>) -> Self {
+    pub fn new(window_options: WindowOptions, global_state: Arc<T>) -> Result<Self, String> {
         let event_loop = EventLoop::new();
         let window = DefaultWindow::new();
         let mut window_builder = WindowBuilder::new()
@@ -302,27 +404,8 @@ impl<T: GlobalStateTrait> Window<T> {
             window_builder = window_builder.with_max_inner_size(max_size);
         }
 
-        let context = ContextBuilder::new()
-            .with_gl(GlRequest::GlThenGles {
-                opengl_version: (3, 2),
-                opengles_version: (3, 0),
-            })
-            .with_vsync(true)
-            .with_double_buffer(Some(true))
-            .with_multisampling(4)
-            .build_windowed(window_builder, &event_loop)
-            .unwrap();
-        let context = unsafe { context.make_current().unwrap() };
-        let gl = match context.get_api() {
-            Api::OpenGl => unsafe {
-                gl::GlFns::load_with(|symbol| context.get_proc_address(symbol) as *const _)
-            },
-            Api::OpenGlEs => unsafe {
-                gl::GlesFns::load_with(|symbol| context.get_proc_address(symbol) as *const _)
-            },
-            Api::WebGl => unimplemented!(),
-        };
-
+        let (context, gl) =
+            Self::create_context(window_builder, &event_loop, window_options.multisampling)?;
         let opts = WebRenderOptions {
             clear_color: ColorF::TRANSPARENT,
             ..WebRenderOptions::default()
@@ -332,13 +415,14 @@ impl<T: GlobalStateTrait> Window<T> {
             DeviceIntSize::new(size.width as i32, size.height as i32)
         };
         let notifier = Box::new(Notifier::new(event_loop.create_proxy()));
-        let (renderer, sender) = create_webrender_instance(gl, notifier, opts, None).unwrap();
+        let (renderer, sender) = create_webrender_instance(gl, notifier, opts, None)
+            .map_err(|error| format!("Failed to initialize the renderer : {:?}", error))?;
         let api = sender.create_api();
         let document_id = api.add_document(device_size);
         let epoch = Epoch(0);
         let pipeline_id = PipelineId(0, 0);
 
-        Window {
+        Ok(Window {
             event_loop,
             wrapper: WindowWrapper::new(
                 window_options.title,
@@ -354,7 +438,93 @@ impl<T: GlobalStateTrait> Window<T> {
                 HashMap::new(),
             ),
             window,
+            line_height: window_options.line_height,
+            scroll_speed: window_options.scroll_speed,
+            shift_horizontal_scroll: window_options.shift_horizontal_scroll,
+        })
+    }
+
+    fn create_context(
+        window_builder: WindowBuilder,
+        event_loop: &EventLoop<()>,
+        multisampling: u16,
+    ) -> Result<(WindowedContext<PossiblyCurrent>, Rc<dyn gl::Gl>), String> {
+        let gl_request = GlRequest::GlThenGles {
+            opengl_version: (3, 2),
+            opengles_version: (3, 0),
+        };
+        let mut attempt_vec = vec![];
+        let mut multisampling = multisampling.min(MAX_MULTISAMPLING).next_power_of_two();
+
+        // from the requested multisampling level down to none
+        while multisampling > 1 {
+            attempt_vec.push((gl_request, multisampling, Some(true)));
+            multisampling /= 2;
         }
+
+        // then from the preferred context to the most compatible one,
+        // the last attempt also accepts software renderers
+        attempt_vec.extend([
+            (gl_request, 0, Some(true)),
+            (GlRequest::Specific(Api::OpenGl, (3, 1)), 0, Some(true)),
+            (GlRequest::Specific(Api::OpenGlEs, (3, 0)), 0, Some(true)),
+            (
+                GlRequest::GlThenGles {
+                    opengl_version: (3, 1),
+                    opengles_version: (3, 0),
+                },
+                0,
+                None,
+            ),
+        ]);
+        let mut error_vec = vec![];
+
+        for (gl_request, multisampling, hardware_acceleration) in attempt_vec {
+            let mut context_builder = ContextBuilder::new()
+                .with_gl(gl_request)
+                .with_vsync(true)
+                .with_double_buffer(Some(true))
+                .with_hardware_acceleration(hardware_acceleration);
+
+            if multisampling > 0 {
+                context_builder = context_builder.with_multisampling(multisampling);
+            }
+
+            let context = match context_builder.build_windowed(window_builder.clone(), event_loop) {
+                Ok(context) => context,
+                Err(error) => {
+                    error_vec.push(format!("{:?} x{} : {}", gl_request, multisampling, error));
+                    continue;
+                }
+            };
+            let context = match unsafe { context.make_current() } {
+                Ok(context) => context,
+                Err((_, error)) => {
+                    error_vec.push(format!("{:?} : {}", gl_request, error));
+                    continue;
+                }
+            };
+            let gl = match context.get_api() {
+                Api::OpenGl => unsafe {
+                    gl::GlFns::load_with(|symbol| context.get_proc_address(symbol) as *const _)
+                },
+                Api::OpenGlEs => unsafe {
+                    gl::GlesFns::load_with(|symbol| context.get_proc_address(symbol) as *const _)
+                },
+                Api::WebGl => {
+                    error_vec.push(format!("{:?} : WebGL is not supported", gl_request));
+                    continue;
+                }
+            };
+
+            return Ok((context, gl));
+        }
+
+        Err(format!(
+            "Failed to create an OpenGL context, OpenGL 3.1+ or OpenGL ES 3.0+ is required, \
+            check that your graphics drivers are installed and up to date.\n\n{}",
+            error_vec.join("\n")
+        ))
     }
 
     pub fn set_window<U: WindowInitTrait<T>>(&mut self) {
@@ -364,11 +534,27 @@ impl<T: GlobalStateTrait> Window<T> {
     }
 
     pub fn run(&mut self) {
-        let mut timer = Timer::new(Duration::from_millis(16));
+        let mut timer = Timer::new(FOCUSED_FRAME_DURATION);
+        let mut last_loop_instant = Instant::now();
 
         loop {
-            let mut exit = false;
+            let loop_instant = Instant::now();
+
+            if self.wrapper.frame_stats.is_enabled() {
+                self.wrapper
+                    .frame_stats
+                    .push_frame_time(loop_instant - last_loop_instant);
+            }
+
+            last_loop_instant = loop_instant;
+
+            let mut force_redraw = false;
+            let mut mouse_moved = false;
             let mut device_motion = PhysicalPosition::new(0.0, 0.0);
+            let mut focused_option = None;
+
+            // the display list may have changed since the last tick
+            self.wrapper.hit_items_option = None;
 
             self.event_loop
                 .run_return(|global_event, _event_loop_window_target, control_flow| {
@@ -399,11 +585,13 @@ impl<T: GlobalStateTrait> Window<T> {
                                     &mut self.wrapper,
                                 );
                                 self.wrapper.update_window_size(size);
-                                self.wrapper.redraw(&mut self.window, true);
-                            }
-                            WindowEvent::CloseRequested => {
-                                exit = true;
+                                force_redraw = true;
                             }
+                            WindowEvent::CloseRequested => self.window.on_event(
+                                Event::CloseRequested,
+                                self.wrapper.do_hit_test(),
+                                &mut self.wrapper,
+                            ),
                             WindowEvent::ReceivedCharacter(char) => {
                                 self.window.on_event(
                                     Event::Char(char),
@@ -412,12 +600,26 @@ impl<T: GlobalStateTrait> Window<T> {
                                 );
                             }
                             WindowEvent::Focused(focused) => {
+                                focused_option = Some(focused);
                                 self.window.on_event(
                                     Event::Focus(focused),
                                     self.wrapper.do_hit_test(),
                                     &mut self.wrapper,
                                 );
                             }
+                            WindowEvent::KeyboardInput {
+                                input:
+                                    KeyboardInput {
+                                        state: ElementState::Pressed,
+                                        virtual_keycode: Some(VirtualKeyCode::F12),
+                                        ..
+                                    },
+                                ..
+                            } => {
+                                // frame statistics overlay
+                                self.wrapper.frame_stats.toggle();
+                                self.wrapper.global_state.request_redraw();
+                            }
                             WindowEvent::KeyboardInput { input, .. } => {
                                 self.window.on_event(
                                     Event::Key(input),
@@ -426,20 +628,22 @@ impl<T: GlobalStateTrait> Window<T> {
                                 );
                             }
                             WindowEvent::CursorMoved { position, .. } => {
-                                self.wrapper.mouse_position = Some(position);
-                                self.window.on_event(
-                                    Event::MousePosition,
-                                    self.wrapper.do_hit_test(),
-                                    &mut self.wrapper,
-                                );
+                                // coalesced, the event is sent once per tick
+                                self.wrapper.set_mouse_position(Some(position));
+                                mouse_moved = true;
                             }
                             WindowEvent::CursorEntered { .. } => self.window.on_event(
                                 Event::MouseEntered,
                                 self.wrapper.do_hit_test(),
                                 &mut self.wrapper,
                             ),
+                            WindowEvent::DroppedFile(path) => self.window.on_event(
+                                Event::FileDropped(path),
+                                self.wrapper.do_hit_test(),
+                                &mut self.wrapper,
+                            ),
                             WindowEvent::CursorLeft { .. } => {
-                                self.wrapper.mouse_position = None;
+                                self.wrapper.set_mouse_position(None);
                                 self.window.on_event(
                                     Event::MouseLeft,
                                     self.wrapper.do_hit_test(),
@@ -451,15 +655,16 @@ impl<T: GlobalStateTrait> Window<T> {
                             } => {
                                 let mut delta = match delta {
                                     MouseScrollDelta::LineDelta(dx, dy) => PhysicalPosition::new(
-                                        (dx * LINE_HEIGHT) as f64,
-                                        (dy * LINE_HEIGHT) as f64,
+                                        (dx * self.line_height * self.scroll_speed) as f64,
+                                        (dy * self.line_height * self.scroll_speed) as f64,
+                                    ),
+                                    MouseScrollDelta::PixelDelta(pos) => PhysicalPosition::new(
+                                        pos.x * self.scroll_speed as f64,
+                                        pos.y * self.scroll_speed as f64,
                                     ),
-                                    MouseScrollDelta::PixelDelta(pos) => {
-                                        PhysicalPosition::new(pos.x, pos.y)
-                                    }
                                 };
 
-                                if modifiers.shift() {
+                                if self.shift_horizontal_scroll && modifiers.shift() {
                                     delta = PhysicalPosition::new(delta.y, delta.x);
                                 }
 
@@ -502,6 +707,14 @@ impl<T: GlobalStateTrait> Window<T> {
                     };
                 });
 
+            if mouse_moved {
+                self.window.on_event(
+                    Event::MousePosition,
+                    self.wrapper.do_hit_test(),
+                    &mut self.wrapper,
+                );
+            }
+
             if device_motion.x != 0.0 || device_motion.y != 0.0 {
                 self.window.on_event(
                     Event::DeviceMotion(device_motion),
@@ -510,16 +723,33 @@ impl<T: GlobalStateTrait> Window<T> {
                 );
             }
 
-            if exit || self.window.should_exit() {
+            if self.window.should_exit() {
                 break;
             }
 
-            self.wrapper.redraw(&mut self.window, false);
+            self.wrapper.redraw(&mut self.window, force_redraw);
+
+            if self.wrapper.frame_stats.is_enabled() {
+                self.wrapper
+                    .frame_stats
+                    .push_loop_time(loop_instant.elapsed());
+            }
+
+            // the loop slows down while the window is in the background,
+            // a new timer is started so no frames are caught up on focus
+            if let Some(focused) = focused_option {
+                timer = Timer::new(if focused {
+                    FOCUSED_FRAME_DURATION
+                } else {
+                    UNFOCUSED_FRAME_DURATION
+                });
+            }
 
             timer.wait();
         }
 
         self.window.unload(&mut self.wrapper);
+        self.wrapper.unload_images();
         self.wrapper.unload_fonts();
     }
 
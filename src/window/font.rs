@@ -1,5 +1,7 @@
+use std::iter::repeat;
 use std::sync::{Arc, Mutex};
 
+use rustybuzz::{Face, UnicodeBuffer};
 use util::thread::MutexTrait;
 use webrender::api::units::{Au, LayoutPoint, LayoutRect, LayoutSize};
 use webrender::api::{
@@ -12,6 +14,7 @@ pub struct Font {
     pub instance_key: FontInstanceKey,
     pub key: FontKey,
     pub size: Au,
+    data: &'static [u8],
     api_mutex: Arc<Mutex<RenderApi>>,
     document_id: DocumentId,
 }
@@ -19,6 +22,7 @@ pub struct Font {
 impl Font {
     pub fn new(
         font_key: FontKey,
+        data: &'static [u8],
         font_size: Au,
         api_mutex: Arc<Mutex<RenderApi>>,
         document_id: DocumentId,
@@ -45,11 +49,14 @@ impl Font {
             instance_key: font_instance_key,
             key: font_key,
             size: font_size,
+            data,
             api_mutex,
             document_id,
         }
     }
 
+    // ascii has one glyph per character, anything else goes through the shaper
+    // so clusters and right to left runs get their real glyphs and advances
     pub fn create_text(&self, text: String, tab_size_option: Option<f32>) -> Text {
         let api = self.api_mutex.lock_poisoned();
         let char_vec: Vec<char> = text.chars().collect();
@@ -58,47 +65,96 @@ impl Font {
         } else {
             4.0
         };
-        let glyph_indices: Vec<u32> = api
-            .get_glyph_indices(self.key, text.as_str())
-            .into_iter()
+        let glyph_vec: Vec<(ShapedGlyph, Option<GlyphDimensions>)> = match (!text.is_ascii())
+            .then(|| Face::from_slice(self.data, 0))
             .flatten()
-            .collect();
-        let glyph_dimension_options =
-            api.get_glyph_dimensions(self.instance_key, glyph_indices.clone());
+        {
+            Some(face) => {
+                let shaped_glyph_vec = shape(&face, text.as_str(), self.size.to_f32_px());
+                let glyph_dimension_vec = api.get_glyph_dimensions(
+                    self.instance_key,
+                    shaped_glyph_vec
+                        .iter()
+                        .map(|shaped_glyph| shaped_glyph.glyph_index)
+                        .collect(),
+                );
+
+                shaped_glyph_vec
+                    .into_iter()
+                    .zip(glyph_dimension_vec.into_iter().chain(repeat(None)))
+                    .collect()
+            }
+            None => {
+                let glyph_indices = api.get_glyph_indices(self.key, text.as_str());
+                let glyph_dimension_vec = api.get_glyph_dimensions(
+                    self.instance_key,
+                    glyph_indices
+                        .iter()
+                        .take(char_vec.len())
+                        .flatten()
+                        .copied()
+                        .collect(),
+                );
+
+                map_glyphs(&char_vec, &glyph_indices, glyph_dimension_vec)
+            }
+        };
         let mut glyph_size = LayoutSize::new(0.0, self.size.to_f32_px());
+        let mut line_width = 0.0f32;
         let mut char_width_mean = 0.0;
         let mut char_width_count = 0;
         let mut max_line_height = 0.0f32;
+        let mut advance_vec = vec![0.0f32; char_vec.len()];
+        let mut has_glyph_vec = vec![false; char_vec.len()];
+        let mut line_height_vec = vec![0.0f32; char_vec.len()];
+
+        for (shaped_glyph, glyph_dimension_option) in glyph_vec.iter() {
+            advance_vec[shaped_glyph.char_index] += shaped_glyph.advance;
+            has_glyph_vec[shaped_glyph.char_index] = true;
 
-        for glyph_dimension_option in glyph_dimension_options.clone() {
             if let Some(glyph_dimension) = glyph_dimension_option {
                 char_width_mean += glyph_dimension.width as f32;
                 char_width_count += 1;
+                line_height_vec[shaped_glyph.char_index] = line_height_vec[shaped_glyph.char_index]
+                    .max(
+                        self.size.to_f32_px() - glyph_dimension.top as f32
+                            + glyph_dimension.height as f32,
+                    );
             }
         }
 
-        char_width_mean /= char_width_count as f32;
+        char_width_mean = if char_width_count > 0 {
+            char_width_mean / char_width_count as f32
+        } else {
+            // no glyph with dimensions, fallback on half the font size
+            self.size.to_f32_px() / 2.0
+        };
 
-        for index in 0..glyph_indices.len() {
-            if let Some(glyph_dimension) = glyph_dimension_options[index] {
-                glyph_size += LayoutSize::new(glyph_dimension.advance, 0.0);
-                max_line_height = max_line_height.max(
-                    self.size.to_f32_px() - glyph_dimension.top as f32
-                        + glyph_dimension.height as f32,
-                );
-            } else {
-                match char_vec[index] {
-                    ' ' => glyph_size += LayoutSize::new(char_width_mean, 0.0),
-                    '\t' => glyph_size += LayoutSize::new(char_width_mean * tab_size, 0.0),
-                    '\n' | '\r' => {
-                        glyph_size += LayoutSize::new(0.0, self.size.to_f32_px());
-                        max_line_height = 0.0;
-                    }
-                    _ => {}
+        // spaces without glyph still need a width
+        for (index, char) in char_vec.iter().enumerate() {
+            if *char == ' ' && !has_glyph_vec[index] {
+                advance_vec[index] = char_width_mean;
+            }
+        }
+
+        for (index, char) in char_vec.iter().enumerate() {
+            match char {
+                '\t' => line_width += tab_advance(line_width, char_width_mean * tab_size),
+                '\n' | '\r' => {
+                    glyph_size.width = glyph_size.width.max(line_width);
+                    glyph_size += LayoutSize::new(0.0, self.size.to_f32_px());
+                    line_width = 0.0;
+                    max_line_height = 0.0;
+                }
+                _ => {
+                    line_width += advance_vec[index];
+                    max_line_height = max_line_height.max(line_height_vec[index]);
                 }
             }
         }
 
+        glyph_size.width = glyph_size.width.max(line_width);
+
         // add extra height on the last line for letters like "g" which goes further down
         if self.size.to_f32_px() != max_line_height {
             glyph_size += LayoutSize::new(0.0, max_line_height - self.size.to_f32_px())
@@ -107,8 +163,11 @@ impl Font {
         Text::new(
             glyph_size,
             char_vec,
-            glyph_indices,
-            glyph_dimension_options,
+            advance_vec,
+            glyph_vec
+                .into_iter()
+                .map(|(shaped_glyph, _)| shaped_glyph)
+                .collect(),
             self.size,
             self.instance_key,
             char_width_mean,
@@ -116,6 +175,113 @@ impl Font {
         )
     }
 
+    // the last space of a line is replaced by a line break when a word overflows,
+    // a word wider than the max width is broken where it overflows,
+    // past the max line count the last line is ellipsized
+    pub fn create_wrapped_text(
+        &self,
+        text: String,
+        max_width: f32,
+        max_line_count_option: Option<usize>,
+    ) -> Text {
+        let measured_text = self.create_text(text, None);
+        let mut wrapped_text = String::new();
+        let mut line_width = 0.0f32;
+        let mut last_space_index_option: Option<usize> = None;
+        let mut width_after_last_space = 0.0f32;
+
+        for (index, char) in measured_text.char_vec.iter().enumerate() {
+            let char_width = measured_text.char_advance(index, line_width);
+
+            match char {
+                '\n' | '\r' => {
+                    line_width = -char_width;
+                    last_space_index_option = None;
+                }
+                ' ' | '\t' => {
+                    last_space_index_option = Some(wrapped_text.len());
+                    width_after_last_space = -char_width;
+                }
+                _ => {
+                    if line_width > 0.0 && line_width + char_width > max_width {
+                        match last_space_index_option.take() {
+                            Some(last_space_index) => {
+                                wrapped_text
+                                    .replace_range(last_space_index..last_space_index + 1, "\n");
+                                line_width = width_after_last_space;
+                            }
+                            None => {
+                                wrapped_text.push('\n');
+                                line_width = 0.0;
+                            }
+                        }
+                    }
+                }
+            }
+
+            wrapped_text.push(*char);
+            line_width += char_width;
+            width_after_last_space += char_width;
+        }
+
+        if let Some(max_line_count) = max_line_count_option {
+            let line_vec: Vec<&str> = wrapped_text.lines().collect();
+
+            if line_vec.len() > max_line_count && max_line_count > 0 {
+                let last_line = self.ellipsize(
+                    &self.create_text(line_vec[max_line_count - 1].to_string(), None),
+                    max_width,
+                );
+
+                wrapped_text = line_vec[..max_line_count - 1]
+                    .iter()
+                    .copied()
+                    .chain([last_line.as_str()])
+                    .collect::<Vec<&str>>()
+                    .join("\n");
+            }
+        }
+
+        self.create_text(wrapped_text, None)
+    }
+
+    // characters are dropped from the end until the text and the ellipsis fit in the max width
+    pub fn create_text_ellipsized(&self, text: String, max_width: f32) -> Text {
+        let measured_text = self.create_text(text, None);
+
+        if measured_text.size.width <= max_width {
+            return measured_text;
+        }
+
+        self.create_text(self.ellipsize(&measured_text, max_width), None)
+    }
+
+    // the first line, shortened to fit in the max width with an ellipsis
+    fn ellipsize(&self, measured_text: &Text, max_width: f32) -> String {
+        let ellipsis_width = self.create_text("…".to_string(), None).size.width;
+        let mut ellipsized_text = String::new();
+        let mut line_width = 0.0f32;
+
+        for (index, char) in measured_text.char_vec.iter().enumerate() {
+            let char_width = measured_text.char_advance(index, line_width);
+
+            if *char == '\n'
+                || *char == '\r'
+                || line_width + char_width + ellipsis_width > max_width
+            {
+                break;
+            }
+
+            ellipsized_text.push(*char);
+            line_width += char_width;
+        }
+
+        ellipsized_text.truncate(ellipsized_text.trim_end().len());
+        ellipsized_text.push('…');
+
+        ellipsized_text
+    }
+
     pub fn unload(&mut self) {
         let mut txn = Transaction::new();
 
@@ -130,9 +296,10 @@ impl Font {
 pub struct Text {
     pub size: LayoutSize,
     pub char_vec: Vec<char>,
-    pub glyph_indices: Vec<u32>,
-    pub glyph_dimension_options: Vec<Option<GlyphDimensions>>,
     pub font_size: Au,
+    // advance of each character, a cluster advance is carried by its first character
+    advance_vec: Vec<f32>,
+    glyph_vec: Vec<ShapedGlyph>,
     instance_key: FontInstanceKey,
     char_width_mean: f32,
     tab_size: f32,
@@ -142,8 +309,8 @@ impl Text {
     fn new(
         size: LayoutSize,
         char_vec: Vec<char>,
-        glyph_indices: Vec<u32>,
-        glyph_dimension_options: Vec<Option<GlyphDimensions>>,
+        advance_vec: Vec<f32>,
+        glyph_vec: Vec<ShapedGlyph>,
         font_size: Au,
         instance_key: FontInstanceKey,
         char_width_mean: f32,
@@ -152,9 +319,9 @@ impl Text {
         Self {
             size,
             char_vec,
-            glyph_indices,
-            glyph_dimension_options,
             font_size,
+            advance_vec,
+            glyph_vec,
             instance_key,
             char_width_mean,
             tab_size,
@@ -169,39 +336,68 @@ impl Text {
         color: ColorF,
         glyph_options: Option<GlyphOptions>,
     ) {
+        self.push_text_aligned(
+            builder,
+            space_and_clip,
+            position,
+            self.size.width,
+            TextAlignment::Left,
+            color,
+            glyph_options,
+        );
+    }
+
+    // each line is aligned inside a box starting at the position and of the given width
+    pub fn push_text_aligned(
+        &self,
+        builder: &mut DisplayListBuilder,
+        space_and_clip: SpaceAndClipInfo,
+        position: LayoutPoint,
+        box_width: f32,
+        alignment: TextAlignment,
+        color: ColorF,
+        glyph_options: Option<GlyphOptions>,
+    ) {
+        let line_offset_vec: Vec<f32> = self
+            .line_widths()
+            .into_iter()
+            .map(|line_width| alignment.offset(box_width, line_width))
+            .collect();
         let mut glyph_instances = vec![];
-        let mut glyph_position = position + LayoutSize::new(0.0, self.font_size.to_f32_px());
-        let mut line_count = 1.0;
+        let mut glyph_iter = self.glyph_vec.iter().peekable();
+        let mut line_index = 0;
+        let mut line_width = 0.0f32;
+
+        for (index, char) in self.char_vec.iter().enumerate() {
+            let glyph_position = position
+                + LayoutSize::new(
+                    line_offset_vec[line_index] + line_width,
+                    self.font_size.to_f32_px() * (line_index + 1) as f32,
+                );
 
-        for (index, glyph_indice) in self.glyph_indices.iter().enumerate() {
-            if let Some(glyph_dimension) = self.glyph_dimension_options[index] {
+            // the glyphs of a run are placed from its first character
+            while let Some(shaped_glyph) =
+                glyph_iter.next_if(|shaped_glyph| shaped_glyph.run_start_index == index)
+            {
                 glyph_instances.push(GlyphInstance {
-                    index: *glyph_indice,
-                    point: glyph_position,
+                    index: shaped_glyph.glyph_index,
+                    point: glyph_position + shaped_glyph.offset,
                 });
-                glyph_position += LayoutSize::new(glyph_dimension.advance, 0.0);
-            } else {
-                match self.char_vec[index] {
-                    ' ' => {
-                        glyph_position += LayoutSize::new(self.char_width_mean, 0.0);
-                    }
-                    '\t' => {
-                        glyph_position +=
-                            LayoutSize::new(self.char_width_mean * self.tab_size, 0.0);
-                    }
-                    '\n' | '\r' => {
-                        glyph_position = position;
-                        glyph_position +=
-                            LayoutSize::new(0.0, self.font_size.to_f32_px() * (line_count + 1.0));
-                        line_count += 1.0;
-                    }
-                    _ => {}
+            }
+
+            match char {
+                '\n' | '\r' => {
+                    line_index += 1;
+                    line_width = 0.0;
                 }
+                _ => line_width += self.char_advance(index, line_width),
             }
         }
 
-        let text_bounds =
-            LayoutRect::from_origin_and_size(position, self.size.to_vector().to_size());
+        let text_bounds = LayoutRect::from_origin_and_size(
+            position,
+            LayoutSize::new(box_width.max(self.size.width), self.size.height),
+        );
 
         builder.push_text(
             &CommonItemProperties::new(text_bounds, space_and_clip),
@@ -212,4 +408,294 @@ impl Text {
             glyph_options,
         );
     }
+
+    // index of the character closest to the x position, on the first line
+    pub fn char_index_at(&self, x: f32) -> usize {
+        let mut line_width = 0.0f32;
+
+        for (index, char) in self.char_vec.iter().enumerate() {
+            if matches!(char, '\n' | '\r') {
+                return index;
+            }
+
+            let char_advance = self.char_advance(index, line_width);
+
+            if x < line_width + char_advance / 2.0 {
+                return index;
+            }
+
+            line_width += char_advance;
+        }
+
+        self.char_vec.len()
+    }
+
+    // tabs go to the next tab stop, line breaks have no width
+    fn char_advance(&self, index: usize, line_width: f32) -> f32 {
+        match self.char_vec[index] {
+            '\t' => tab_advance(line_width, self.char_width_mean * self.tab_size),
+            '\n' | '\r' => 0.0,
+            _ => self.advance_vec[index],
+        }
+    }
+
+    // width of every line, same advances as when the text is drawn
+    fn line_widths(&self) -> Vec<f32> {
+        let mut line_width_vec = vec![];
+        let mut line_width = 0.0f32;
+
+        for (index, char) in self.char_vec.iter().enumerate() {
+            match char {
+                '\n' | '\r' => {
+                    line_width_vec.push(line_width);
+                    line_width = 0.0;
+                }
+                _ => line_width += self.char_advance(index, line_width),
+            }
+        }
+
+        line_width_vec.push(line_width);
+        line_width_vec
+    }
+}
+
+// a glyph placed from the first character of its run
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct ShapedGlyph {
+    glyph_index: u32,
+    // first character of the glyph cluster, it carries the glyph advance
+    char_index: usize,
+    run_start_index: usize,
+    advance: f32,
+    offset: LayoutSize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TextAlignment {
+    Left,
+    Center,
+    Right,
+}
+
+impl TextAlignment {
+    fn offset(&self, box_width: f32, line_width: f32) -> f32 {
+        match self {
+            TextAlignment::Left => 0.0,
+            TextAlignment::Center => (box_width - line_width) / 2.0,
+            TextAlignment::Right => box_width - line_width,
+        }
+    }
+}
+
+// distance to the next tab stop, tab stops are relative to the line start
+fn tab_advance(line_width: f32, tab_width: f32) -> f32 {
+    if tab_width > 0.0 {
+        ((line_width / tab_width).floor() + 1.0) * tab_width - line_width
+    } else {
+        0.0
+    }
+}
+
+// ascii fast path, each character is its own run, characters without glyph or dimensions
+// are not drawn, the api can answer with a different count than asked
+fn map_glyphs(
+    char_vec: &[char],
+    glyph_indices: &[Option<u32>],
+    glyph_dimension_vec: Vec<Option<GlyphDimensions>>,
+) -> Vec<(ShapedGlyph, Option<GlyphDimensions>)> {
+    let mut glyph_dimension_iter = glyph_dimension_vec.into_iter();
+    let mut glyph_vec = vec![];
+
+    for (index, (char, glyph_index_option)) in char_vec.iter().zip(glyph_indices).enumerate() {
+        if let Some(glyph_index) = glyph_index_option {
+            if let Some(glyph_dimension) = glyph_dimension_iter.next().flatten() {
+                // laid out by the text, whatever the font has for them
+                if matches!(char, '\t' | '\n' | '\r') {
+                    continue;
+                }
+
+                glyph_vec.push((
+                    ShapedGlyph {
+                        glyph_index: *glyph_index,
+                        char_index: index,
+                        run_start_index: index,
+                        advance: glyph_dimension.advance,
+                        offset: LayoutSize::zero(),
+                    },
+                    Some(glyph_dimension),
+                ));
+            }
+        }
+    }
+
+    glyph_vec
+}
+
+// only what is between tabs and line breaks is shaped, glyphs come out in visual order
+// so right to left runs are drawn right to left, the direction is guessed for each run
+fn shape(face: &Face, text: &str, font_size: f32) -> Vec<ShapedGlyph> {
+    let scale = font_size / face.units_per_em() as f32;
+    let mut shaped_glyph_vec = vec![];
+    let mut run_start_index = 0;
+
+    for run in text.split(|char: char| matches!(char, '\t' | '\n' | '\r')) {
+        if !run.is_empty() {
+            let mut unicode_buffer = UnicodeBuffer::new();
+
+            unicode_buffer.push_str(run);
+            unicode_buffer.guess_segment_properties();
+
+            let glyph_buffer = rustybuzz::shape(face, &[], unicode_buffer);
+            let char_byte_index_vec: Vec<usize> = run
+                .char_indices()
+                .map(|(byte_index, _)| byte_index)
+                .collect();
+            let mut pen = 0.0;
+
+            for (glyph_info, glyph_position) in glyph_buffer
+                .glyph_infos()
+                .iter()
+                .zip(glyph_buffer.glyph_positions())
+            {
+                let advance = glyph_position.x_advance as f32 * scale;
+
+                shaped_glyph_vec.push(ShapedGlyph {
+                    glyph_index: glyph_info.glyph_id,
+                    // clusters are byte indices in the run
+                    char_index: run_start_index
+                        + char_byte_index_vec.partition_point(|byte_index| {
+                            *byte_index < glyph_info.cluster as usize
+                        }),
+                    run_start_index,
+                    advance,
+                    offset: LayoutSize::new(
+                        pen + glyph_position.x_offset as f32 * scale,
+                        -glyph_position.y_offset as f32 * scale,
+                    ),
+                });
+                pen += advance;
+            }
+        }
+
+        // skip the run and its separator
+        run_start_index += run.chars().count() + 1;
+    }
+
+    shaped_glyph_vec
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPEN_SANS: &[u8] = include_bytes!("../../ui/font/OpenSans.ttf");
+
+    fn glyph_dimensions(advance: f32) -> GlyphDimensions {
+        GlyphDimensions {
+            left: 0,
+            top: 0,
+            width: advance as i32,
+            height: 10,
+            advance,
+        }
+    }
+
+    #[test]
+    fn tab_advance_goes_to_the_next_tab_stop() {
+        assert_eq!(tab_advance(0.0, 40.0), 40.0);
+        assert_eq!(tab_advance(10.0, 40.0), 30.0);
+        assert_eq!(tab_advance(40.0, 40.0), 40.0);
+        assert_eq!(tab_advance(95.0, 40.0), 25.0);
+        assert_eq!(tab_advance(10.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn text_alignment_offset() {
+        assert_eq!(TextAlignment::Left.offset(100.0, 40.0), 0.0);
+        assert_eq!(TextAlignment::Center.offset(100.0, 40.0), 30.0);
+        assert_eq!(TextAlignment::Right.offset(100.0, 40.0), 60.0);
+    }
+
+    #[test]
+    fn map_glyphs_keeps_glyphs_on_their_character() {
+        let glyph_vec = map_glyphs(
+            &['a', '\t', 'b', 'c', 'd'],
+            &[Some(1), Some(2), None, Some(3), Some(4)],
+            vec![
+                Some(glyph_dimensions(5.0)),
+                Some(glyph_dimensions(20.0)),
+                None,
+                Some(glyph_dimensions(7.0)),
+            ],
+        );
+        let glyph_info_vec: Vec<(usize, u32, f32)> = glyph_vec
+            .iter()
+            .map(|(shaped_glyph, _)| {
+                (
+                    shaped_glyph.char_index,
+                    shaped_glyph.glyph_index,
+                    shaped_glyph.advance,
+                )
+            })
+            .collect();
+
+        // the tab has no glyph drawn, 'b' has no index and 'c' no dimensions
+        assert_eq!(glyph_info_vec, vec![(0, 1, 5.0), (4, 4, 7.0)]);
+    }
+
+    #[test]
+    fn map_glyphs_stops_when_dimensions_are_missing() {
+        let glyph_vec = map_glyphs(
+            &['a', 'b', 'c'],
+            &[Some(1), Some(2), Some(3)],
+            vec![Some(glyph_dimensions(5.0))],
+        );
+
+        assert_eq!(glyph_vec.len(), 1);
+        assert_eq!(glyph_vec[0].0.char_index, 0);
+    }
+
+    #[test]
+    fn shape_gives_a_cluster_to_its_first_character() {
+        let face = Face::from_slice(OPEN_SANS, 0).unwrap();
+        let shaped_glyph_vec = shape(&face, "e\u{301}x", 13.0);
+
+        // the combining mark never carries a glyph of its own
+        assert!(shaped_glyph_vec
+            .iter()
+            .all(|shaped_glyph| shaped_glyph.char_index != 1));
+        assert_eq!(shaped_glyph_vec.last().unwrap().char_index, 2);
+        assert!(shaped_glyph_vec
+            .iter()
+            .all(|shaped_glyph| shaped_glyph.run_start_index == 0));
+    }
+
+    #[test]
+    fn shape_starts_a_run_after_tabs_and_line_breaks() {
+        let face = Face::from_slice(OPEN_SANS, 0).unwrap();
+        let shaped_glyph_vec = shape(&face, "é\tb\nc", 13.0);
+        let run_start_index_vec: Vec<usize> = shaped_glyph_vec
+            .iter()
+            .map(|shaped_glyph| shaped_glyph.run_start_index)
+            .collect();
+
+        assert_eq!(run_start_index_vec, vec![0, 2, 4]);
+        assert!(shaped_glyph_vec
+            .iter()
+            .all(|shaped_glyph| shaped_glyph.offset.width == 0.0));
+    }
+
+    #[test]
+    fn shape_outputs_right_to_left_runs_in_visual_order() {
+        let face = Face::from_slice(OPEN_SANS, 0).unwrap();
+        let shaped_glyph_vec = shape(&face, "אבגד", 13.0);
+
+        assert_eq!(shaped_glyph_vec.first().unwrap().char_index, 3);
+        assert_eq!(shaped_glyph_vec.last().unwrap().char_index, 0);
+
+        // drawn from left to right from the run start
+        for pair in shaped_glyph_vec.windows(2) {
+            assert!(pair[0].offset.width <= pair[1].offset.width);
+        }
+    }
 }
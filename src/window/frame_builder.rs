@@ -1,5 +1,8 @@
-use webrender::api::units::{DeviceIntSize, LayoutRect, LayoutSize};
-use webrender::api::{DisplayListBuilder, SpaceAndClipInfo};
+use webrender::api::units::{DeviceIntSize, LayoutRect, LayoutSize, LayoutVector2D};
+use webrender::api::{
+    APZScrollGeneration, DisplayListBuilder, ExternalScrollId, HasScrollLinkedEffect, PipelineId,
+    SpaceAndClipInfo, SpatialTreeItemKey,
+};
 use webrender::euclid::Scale;
 
 use super::{GlobalStateTrait, WindowWrapper};
@@ -36,4 +39,36 @@ impl FrameBuilder {
             bounds,
         }
     }
+
+    // define a scroll frame clipped to the frame rect, returns the space and clip
+    // in which the scrolled content is drawn, the id is the external scroll id
+    pub fn push_scroll_region(
+        &mut self,
+        space_and_clip: SpaceAndClipInfo,
+        id: u64,
+        content_size: LayoutSize,
+        frame_layout_rect: LayoutRect,
+        offset: LayoutVector2D,
+    ) -> SpaceAndClipInfo {
+        let spatial_id = self.builder.define_scroll_frame(
+            space_and_clip.spatial_id,
+            ExternalScrollId(id, PipelineId::dummy()),
+            LayoutRect::from_origin_and_size(frame_layout_rect.min, content_size),
+            frame_layout_rect,
+            offset,
+            APZScrollGeneration::default(),
+            HasScrollLinkedEffect::No,
+            SpatialTreeItemKey::new(0, id),
+        );
+        let clip_id = self
+            .builder
+            .define_clip_rect(space_and_clip.spatial_id, frame_layout_rect);
+
+        SpaceAndClipInfo {
+            spatial_id,
+            clip_chain_id: self
+                .builder
+                .define_clip_chain(Some(space_and_clip.clip_chain_id), [clip_id]),
+        }
+    }
 }
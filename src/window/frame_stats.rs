@@ -0,0 +1,104 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+const SAMPLE_COUNT: usize = 60;
+
+// rolling averages of the last frames, nothing is measured while disabled
+pub struct FrameStats {
+    enabled: bool,
+    frame_time_vec: VecDeque<Duration>,
+    loop_time_vec: VecDeque<Duration>,
+    build_time_vec: VecDeque<Duration>,
+    // display lists built during the last second
+    build_instant_vec: VecDeque<Instant>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            frame_time_vec: VecDeque::with_capacity(SAMPLE_COUNT),
+            loop_time_vec: VecDeque::with_capacity(SAMPLE_COUNT),
+            build_time_vec: VecDeque::with_capacity(SAMPLE_COUNT),
+            build_instant_vec: VecDeque::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        self.frame_time_vec.clear();
+        self.loop_time_vec.clear();
+        self.build_time_vec.clear();
+        self.build_instant_vec.clear();
+    }
+
+    // time between two loop iterations, including the wait for the next tick
+    pub fn push_frame_time(&mut self, duration: Duration) {
+        push_sample(&mut self.frame_time_vec, duration);
+    }
+
+    // time spent in a loop iteration
+    pub fn push_loop_time(&mut self, duration: Duration) {
+        push_sample(&mut self.loop_time_vec, duration);
+    }
+
+    // time spent building the display list
+    pub fn push_build_time(&mut self, duration: Duration) {
+        push_sample(&mut self.build_time_vec, duration);
+
+        while let Some(build_instant) = self.build_instant_vec.front() {
+            if build_instant.elapsed() <= Duration::from_secs(1) {
+                break;
+            }
+
+            self.build_instant_vec.pop_front();
+        }
+
+        self.build_instant_vec.push_back(Instant::now());
+    }
+
+    pub fn fps(&self) -> f32 {
+        let frame_time = average(&self.frame_time_vec).as_secs_f32();
+
+        if frame_time > 0.0 {
+            1.0 / frame_time
+        } else {
+            0.0
+        }
+    }
+
+    pub fn loop_time(&self) -> Duration {
+        average(&self.loop_time_vec)
+    }
+
+    pub fn build_time(&self) -> Duration {
+        average(&self.build_time_vec)
+    }
+
+    pub fn builds_per_second(&self) -> usize {
+        self.build_instant_vec
+            .iter()
+            .filter(|build_instant| build_instant.elapsed() <= Duration::from_secs(1))
+            .count()
+    }
+}
+
+fn push_sample(sample_vec: &mut VecDeque<Duration>, duration: Duration) {
+    if sample_vec.len() == SAMPLE_COUNT {
+        sample_vec.pop_front();
+    }
+
+    sample_vec.push_back(duration);
+}
+
+fn average(sample_vec: &VecDeque<Duration>) -> Duration {
+    if sample_vec.is_empty() {
+        return Duration::ZERO;
+    }
+
+    sample_vec.iter().sum::<Duration>() / sample_vec.len() as u32
+}